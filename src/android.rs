@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::fs;
+use std::fmt::Write;
+use serde_json::Value;
+use failure::Fallible;
+use crate::dataset::{self, CATEGORIES};
+use crate::personality::Personality;
+use crate::species::Species;
+use crate::location::{Place, Modifier};
+
+/// Fields holding per-language text maps, keyed by their serialized name.
+const LOCALIZED_FIELDS: &[&str] = &["name", "phrase", "photo_phrase", "catch_phrase", "museum_description"];
+
+/// Languages of the personality, species and location label tables
+const LABEL_LANGUAGES: &[&str] = &["eng", "deu"];
+
+/// Language used for the default `values` folder.
+const DEFAULT_LOCALE: &str = "en";
+
+/// Writes a `strings.xml` per locale into `res_dir` (e.g. `values/strings.xml`, `values-de/strings.xml`).
+pub fn export_strings(data_dir: impl AsRef<Path>, res_dir: impl AsRef<Path>) -> Fallible<()> {
+    let data_dir = data_dir.as_ref();
+    let res_dir = res_dir.as_ref();
+
+    // locale => resource key => text
+    let mut locales = BTreeMap::<String, BTreeMap<String, String>>::new();
+
+    for category in CATEGORIES {
+        for item in dataset::load(data_dir, category)? {
+            let id = match dataset::id(&item) {
+                Some(id) => id,
+                None => continue,
+            };
+
+            for &field in LOCALIZED_FIELDS {
+                let texts = match item.get(field).and_then(Value::as_object) {
+                    Some(texts) => texts,
+                    None => continue,
+                };
+
+                for (language, text) in texts {
+                    let text = match text.as_str() {
+                        Some(text) if !text.is_empty() => text,
+                        _ => continue,
+                    };
+                    let key = format!("{}_{}_{}", category.name, field, id);
+
+                    locales
+                        .entry(locale(language).into())
+                        .or_default()
+                        .insert(key, text.into());
+                }
+            }
+        }
+    }
+
+    for &language in LABEL_LANGUAGES {
+        let strings = locales.entry(locale(language).into()).or_default();
+
+        for personality in Personality::all() {
+            if let Some(label) = personality.label(language) {
+                strings.insert(format!("personality_{}", personality.code()), label.into());
+            }
+        }
+
+        for species in Species::all() {
+            if let Some(label) = species.label(language) {
+                strings.insert(format!("species_{}", species.code()), label.into());
+            }
+        }
+
+        for place in Place::all() {
+            if let Some(label) = place.label(language) {
+                strings.insert(format!("place_{}", place.code()), label.into());
+            }
+        }
+
+        for modifier in Modifier::all() {
+            if let Some(label) = modifier.label(language) {
+                strings.insert(format!("location_modifier_{}", modifier.code()), label.into());
+            }
+        }
+    }
+
+    for (locale, strings) in &locales {
+        let folder = if locale == DEFAULT_LOCALE {
+            "values".to_owned()
+        } else {
+            format!("values-{}", locale)
+        };
+        let folder = res_dir.join(folder);
+
+        println!("Writing {} strings to '{}'", strings.len(), folder.display());
+
+        fs::create_dir_all(&folder)?;
+        fs::write(folder.join("strings.xml"), render(strings)?)?;
+    }
+
+    Ok(())
+}
+
+/// Maps the language keys used in the data to Android locale qualifiers.
+fn locale(language: &str) -> &str {
+    match language {
+        "en" | "eng" => "en",
+        "de" | "deu" | "ger" => "de",
+        "fr" | "fra" | "fre" => "fr",
+        "es" | "spa" => "es",
+        "it" | "ita" => "it",
+        "nl" | "nld" | "dut" => "nl",
+        "ru" | "rus" => "ru",
+        "ja" | "jpn" => "ja",
+        "ko" | "kor" => "ko",
+        "zh" | "zho" | "chi" => "zh",
+        language => language,
+    }
+}
+
+fn render(strings: &BTreeMap<String, String>) -> Fallible<String> {
+    let mut xml = String::new();
+
+    writeln!(xml, r#"<?xml version="1.0" encoding="utf-8"?>"#)?;
+    writeln!(xml, "<resources>")?;
+
+    for (key, text) in strings {
+        writeln!(xml, r#"    <string name="{}">{}</string>"#, key, escape(text))?;
+    }
+
+    writeln!(xml, "</resources>")?;
+
+    Ok(xml)
+}
+
+/// Escapes text according to Android's string resource rules.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '\'' => escaped.push_str("\\'"),
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '@' | '?' if i == 0 => {
+                escaped.push('\\');
+                escaped.push(c);
+            },
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
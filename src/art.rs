@@ -1,25 +1,90 @@
 use std::collections::BTreeMap;
+use std::path::Path;
+use image::imageops::{self, FilterType};
 use select::{node::Node, predicate::*};
 use serde::*;
 use failure::{Fallible};
 use itertools::Itertools;
 use crate::common::*;
+use crate::pipeline::{Pipeline, Fit};
 use crate::id;
+use crate::artworks::{self, Artwork};
+use crate::manifest::Manifest;
+use crate::prices::Prices;
 
 #[derive(Debug, Serialize)]
 pub struct Art {
     pub id: usize,
     #[serde(rename="name")]
     pub names: BTreeMap<String, String>,
-    pub kind: &'static str,
+    pub kind: ArtKind,
+    /// Bought from Redd, sold at Nook's Cranny
+    pub prices: Prices,
     pub fake_exists: bool,
     #[serde(skip)]
     pub fake_image_url: Option<String>,
     pub fake_description: BTreeMap<String, String>,
+    /// Area in which the downloaded fake image differs from the real one
+    pub diff_region: Option<Region>,
+    /// The real-world artwork this piece is based on
+    pub artwork: Option<Artwork>,
     #[serde(skip)]
     pub image_url: Option<String>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArtKind {
+    Statue,
+    Painting,
+    /// Any kind not known yet, e.g. from future updates
+    Other(String),
+}
+
+impl ArtKind {
+    fn from_name(english_name: &str) -> Self {
+        let english_name = english_name.to_lowercase();
+
+        if english_name.contains("statue") {
+            ArtKind::Statue
+        } else if english_name.contains("painting") {
+            ArtKind::Painting
+        } else {
+            let kind = english_name
+                .split_whitespace()
+                .last()
+                .unwrap_or("unknown");
+
+            ArtKind::Other(kind.into())
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ArtKind::Statue => "statue",
+            ArtKind::Painting => "painting",
+            ArtKind::Other(kind) => kind,
+        }
+    }
+}
+
+impl Serialize for ArtKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Region {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Pixels whose summed RGBA difference exceeds this are considered different.
+/// Leaves room for scaling and compression artifacts.
+const DIFF_THRESHOLD: u32 = 96;
+
 pub fn fetch_all() -> Fallible<Vec<Art>> {
     let page = download_page("https://animalcrossingwiki.de/acnh/reiner")?;
 
@@ -36,9 +101,143 @@ pub fn fetch_all() -> Fallible<Vec<Art>> {
         all_art.extend(art)
     }
 
+    let english_info = fetch_english_info()?;
+
+    for art in &mut all_art {
+        let name = art.names["eng"].to_lowercase();
+
+        if let Some(info) = english_info.get(&name) {
+            if let Some(description) = &info.fake_description {
+                art.fake_description.insert("eng".into(), description.clone());
+            }
+
+            art.prices = info.prices;
+        }
+    }
+
     Ok(all_art)
 }
 
+/// What the English wiki knows about a piece of art that the german one does not.
+#[derive(Default)]
+struct EnglishInfo {
+    fake_description: Option<String>,
+    /// Bought from Redd, sold at Nook's Cranny
+    prices: Prices,
+}
+
+/// Returns the fake descriptions and prices of the English wiki, keyed by lowercase english name.
+fn fetch_english_info() -> Fallible<BTreeMap<String, EnglishInfo>> {
+    let page = download_page("https://animalcrossing.fandom.com/wiki/Art_(New_Horizons)")?;
+    let mut infos = BTreeMap::<_, EnglishInfo>::new();
+
+    for table in page.find(Name("table")) {
+        let headers = table.find(Name("th"))
+            .map(|header| header.text().trim().to_lowercase())
+            .collect_vec();
+        let description_index = headers.iter().position(|header|
+            header.contains("fake") || header.contains("forgery") || header.contains("difference")
+        );
+        let buy_index = headers.iter().position(|header| header.contains("buy"));
+        let sell_index = headers.iter().position(|header| header.contains("sell"));
+
+        if description_index.is_none() && buy_index.is_none() && sell_index.is_none() {
+            continue;
+        }
+
+        for row in table.find(Name("tr")) {
+            let cols = row.find(Name("td")).collect_vec();
+            let col = |index: Option<usize>| index.and_then(|index| cols.get(index)).map(|col| col.text());
+
+            let name = match cols.get(0).and_then(|name| parse_text(name.text())) {
+                Some(name) => name,
+                None => continue,
+            };
+            let description = col(description_index)
+                .and_then(parse_text)
+                .filter(|description| !description.is_empty());
+            let buy = col(buy_index).and_then(parse_price);
+            let sell = col(sell_index).and_then(parse_price);
+
+            if description.is_none() && buy.is_none() && sell.is_none() {
+                continue;
+            }
+
+            let info = infos.entry(name.to_lowercase()).or_default();
+            info.fake_description = description.or_else(|| info.fake_description.take());
+            info.prices = Prices::sell(sell.or(info.prices.sell)).with_buy(buy.or(info.prices.buy));
+        }
+    }
+
+    Ok(infos)
+}
+
+/// Compares the downloaded real and fake images of every forgery,
+/// sets `diff_region` and writes a highlighted overlay to `art{id}_diff.png`.
+pub fn compute_diff_regions(all_art: &mut [Art], image_dir: impl AsRef<Path>) -> Fallible<()> {
+    let image_dir = image_dir.as_ref();
+    let manifest = Manifest::load(image_dir)?;
+
+    for art in all_art {
+        if !art.fake_exists {
+            continue;
+        }
+
+        let real_path = image_dir.join(manifest.image_path(&format!("art{}", art.id)));
+        let fake_path = image_dir.join(manifest.image_path(&format!("art{}_fake", art.id)));
+
+        if let Some(missing) = [&real_path, &fake_path].iter().find(|path| !path.exists()) {
+            eprintln!("Missing image '{}', skipping the difference of '{}'", missing.display(), art.names["eng"]);
+            continue;
+        }
+
+        let real = image::open(real_path)?.to_rgba8();
+        let mut fake = image::open(fake_path)?.to_rgba8();
+
+        if fake.dimensions() != real.dimensions() {
+            fake = imageops::resize(&fake, real.width(), real.height(), FilterType::Lanczos3);
+        }
+
+        let mut overlay = real.clone();
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+
+        for (x, y, real_pixel) in real.enumerate_pixels() {
+            let fake_pixel = fake.get_pixel(x, y);
+            let difference = real_pixel.0.iter()
+                .zip(&fake_pixel.0)
+                .map(|(&a, &b)| (a.max(b) - a.min(b)) as u32)
+                .sum::<u32>();
+
+            if difference > DIFF_THRESHOLD {
+                min_x = min_x.min(x);
+                min_y = min_y.min(y);
+                max_x = max_x.max(x);
+                max_y = max_y.max(y);
+
+                let pixel = overlay.get_pixel_mut(x, y);
+                pixel.0 = [255, pixel.0[1] / 2, pixel.0[2] / 2, 255];
+            }
+        }
+
+        if min_x > max_x {
+            println!("No difference found between real and fake '{}'", art.names["eng"]);
+            art.diff_region = None;
+            continue;
+        }
+
+        art.diff_region = Some(Region {
+            x: min_x,
+            y: min_y,
+            width: max_x - min_x + 1,
+            height: max_y - min_y + 1,
+        });
+
+        overlay.save(image_dir.join(format!("art{}_diff.png", art.id)))?;
+    }
+
+    Ok(())
+}
+
 fn parse_table(table: &Node) -> Fallible<Vec<Art>> {
     let rows = table.find(Name("tr"));
 
@@ -108,9 +307,13 @@ fn parse_table(table: &Node) -> Fallible<Vec<Art>> {
             _ => continue,
         }
 
-        let kind = if english_name.contains("statue") { "statue" }
-            else if english_name.contains("painting") { "painting" }
-            else { panic!("Unknown art kind: '{}'", english_name) };
+        let kind = ArtKind::from_name(&english_name);
+
+        if let ArtKind::Other(kind) = &kind {
+            eprintln!("Unknown art kind '{}' of '{}'", kind, english_name);
+        }
+
+        let artwork = artworks::artwork(&english_name);
 
         // Fix some missing names
         german_name = match &*english_name {
@@ -131,7 +334,10 @@ fn parse_table(table: &Node) -> Fallible<Vec<Art>> {
             names,
             fake_exists,
             fake_description,
+            diff_region: None,
+            artwork,
             kind,
+            prices: Prices::default(),
             fake_image_url: fake_img,
             image_url: img,
         };
@@ -148,17 +354,21 @@ impl HasFiles for Art {
 
         if let Some(image_url) = &self.image_url {
             files.push(File {
-                name: format!("art{}.png", self.id),
+                name: format!("art{}", self.id),
+                id: self.id,
+                category: "art",
                 url: image_url.clone(),
-                transform: convert_image_to_png,
+                pipeline: Pipeline::default().with_fit(Fit::Pad),
             })
         }
 
         if let Some(fake_image_url) = &self.fake_image_url {
             files.push(File {
-                name: format!("art{}_fake.png", self.id),
+                name: format!("art{}_fake", self.id),
+                id: self.id,
+                category: "art",
                 url: fake_image_url.clone(),
-                transform: convert_image_to_png,
+                pipeline: Pipeline::default().with_fit(Fit::Pad),
             })
         }
 
@@ -0,0 +1,72 @@
+use serde::*;
+
+/// The real-world artwork an art piece is based on.
+#[derive(Debug, Clone, Serialize)]
+pub struct Artwork {
+    pub title: &'static str,
+    pub artist: &'static str,
+    pub year: &'static str,
+    pub medium: &'static str,
+}
+
+/// Looks up the artwork by the art piece's english name, with a warning if it is not known.
+pub fn artwork(name: impl AsRef<str>) -> Option<Artwork> {
+    let name = name.as_ref().to_lowercase();
+
+    let artwork = ARTWORKS.iter()
+        .find(|&&(other, ..)| other == name)
+        .map(|&(_, title, artist, year, medium)| Artwork { title, artist, year, medium });
+
+    if artwork.is_none() {
+        eprintln!("Unknown artwork of '{}'", name);
+    }
+
+    artwork
+}
+
+/// (name, title, artist, year, medium)
+const ARTWORKS: &[(&str, &str, &str, &str, &str)] = &[
+    ("robust statue", "Discobolus", "Myron", "c. 460–450 BC", "Bronze, known from Roman marble copies"),
+    ("rock-head statue", "Olmec Colossal Head", "Olmec civilization", "c. 1200–900 BC", "Basalt"),
+    ("beautiful statue", "Venus de Milo", "Alexandros of Antioch", "c. 150–125 BC", "Marble"),
+    ("valiant statue", "Winged Victory of Samothrace", "Unknown", "c. 190 BC", "Marble"),
+    ("gallant statue", "David", "Michelangelo", "1501–1504", "Marble"),
+    ("mystic statue", "Nefertiti Bust", "Thutmose", "c. 1345 BC", "Limestone and stucco"),
+    ("informative statue", "Rosetta Stone", "Unknown", "196 BC", "Granodiorite"),
+    ("warrior statue", "Terracotta Army", "Unknown", "c. 210 BC", "Terracotta"),
+    ("tremendous statue", "Flame-style Pot", "Unknown (Jōmon period)", "c. 3000–2000 BC", "Earthenware"),
+    ("ancient statue", "Shakōki-dogū", "Unknown (Jōmon period)", "c. 1000–400 BC", "Earthenware"),
+    ("motherly statue", "Capitoline Wolf", "Unknown", "c. 11th–12th century", "Bronze"),
+    ("familiar statue", "The Thinker", "Auguste Rodin", "1904", "Bronze"),
+    ("great statue", "King Kamehameha I", "Thomas Ridgway Gould", "1880", "Bronze"),
+    ("quaint painting", "The Milkmaid", "Johannes Vermeer", "c. 1658", "Oil on canvas"),
+    ("graceful painting", "Beauty Looking Back", "Hishikawa Moronobu", "c. 1690", "Ink and color on silk"),
+    ("famous painting", "Mona Lisa", "Leonardo da Vinci", "c. 1503–1519", "Oil on poplar panel"),
+    ("detailed painting", "Hydrangeas and Swallow", "Itō Jakuchū", "c. 1757–1766", "Ink and color on silk"),
+    ("basic painting", "The Blue Boy", "Thomas Gainsborough", "c. 1770", "Oil on canvas"),
+    ("serene painting", "Lady with an Ermine", "Leonardo da Vinci", "c. 1489–1491", "Oil on walnut panel"),
+    ("amazing painting", "The Night Watch", "Rembrandt", "1642", "Oil on canvas"),
+    ("solemn painting", "Las Meninas", "Diego Velázquez", "1656", "Oil on canvas"),
+    ("scary painting", "Ōtani Oniji III as Yakko Edobei", "Tōshūsai Sharaku", "1794", "Woodblock print"),
+    ("jolly painting", "Summer", "Giuseppe Arcimboldo", "1563", "Oil on canvas"),
+    ("wistful painting", "Girl with a Pearl Earring", "Johannes Vermeer", "c. 1665", "Oil on canvas"),
+    ("moving painting", "The Birth of Venus", "Sandro Botticelli", "c. 1484–1486", "Tempera on canvas"),
+    ("wild painting left half", "Wind God and Thunder God", "Tawaraya Sōtatsu", "17th century", "Ink and color on gold-leafed paper"),
+    ("wild painting right half", "Wind God and Thunder God", "Tawaraya Sōtatsu", "17th century", "Ink and color on gold-leafed paper"),
+    ("scenic painting", "Hunters in the Snow", "Pieter Bruegel the Elder", "1565", "Oil on wood"),
+    ("academic painting", "Vitruvian Man", "Leonardo da Vinci", "c. 1490", "Pen and ink on paper"),
+    ("common painting", "The Gleaners", "Jean-François Millet", "1857", "Oil on canvas"),
+    ("flowery painting", "Sunflowers", "Vincent van Gogh", "1888", "Oil on canvas"),
+    ("twinkling painting", "The Starry Night", "Vincent van Gogh", "1889", "Oil on canvas"),
+    ("nice painting", "The Fifer", "Édouard Manet", "1866", "Oil on canvas"),
+    ("moody painting", "The Sower", "Jean-François Millet", "1850", "Oil on canvas"),
+    ("glowing painting", "The Fighting Temeraire", "J. M. W. Turner", "1839", "Oil on canvas"),
+    ("perfect painting", "Apples and Oranges", "Paul Cézanne", "c. 1899", "Oil on canvas"),
+    ("mysterious painting", "Isle of the Dead", "Arnold Böcklin", "1883", "Oil on wood"),
+    ("calm painting", "A Sunday Afternoon on the Island of La Grande Jatte", "Georges Seurat", "1884–1886", "Oil on canvas"),
+    ("proper painting", "A Bar at the Folies-Bergère", "Édouard Manet", "1882", "Oil on canvas"),
+    ("sinking painting", "Ophelia", "John Everett Millais", "1851–1852", "Oil on canvas"),
+    ("worthy painting", "Liberty Leading the People", "Eugène Delacroix", "1830", "Oil on canvas"),
+    ("warm painting", "The Clothed Maja", "Francisco Goya", "c. 1800–1805", "Oil on canvas"),
+    ("dynamic painting", "The Great Wave off Kanagawa", "Katsushika Hokusai", "c. 1831", "Woodblock print"),
+];
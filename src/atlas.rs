@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::fs;
+use serde::*;
+use serde_json as json;
+use image::{RgbaImage, imageops};
+use failure::Fallible;
+use crate::dataset::{self, Category, CATEGORIES};
+use crate::manifest::Manifest;
+
+/// Maximum width and height of a sprite sheet.
+const MAX_SHEET_SIZE: u32 = 2048;
+
+/// Transparent gap between sprites, avoids bleeding when sampling with filtering.
+const PADDING: u32 = 2;
+
+#[derive(Debug, Serialize)]
+pub struct AtlasIndex {
+    pub sheets: Vec<String>,
+    /// Keyed by item ID
+    pub sprites: BTreeMap<u64, Sprite>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Sprite {
+    pub sheet: usize,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+struct Icon {
+    id: u64,
+    image: RgbaImage,
+}
+
+/// Packs the downloaded icons of every category into sprite sheets
+/// and writes `{category}_{n}.png` plus a `{category}.json` index into `out_dir`.
+pub fn generate(data_dir: impl AsRef<Path>, image_dir: impl AsRef<Path>, out_dir: impl AsRef<Path>) -> Fallible<()> {
+    let out_dir = out_dir.as_ref();
+    let manifest = Manifest::load(image_dir.as_ref())?;
+
+    fs::create_dir_all(out_dir)?;
+
+    for category in CATEGORIES {
+        let icons = load_icons(category, data_dir.as_ref(), image_dir.as_ref(), &manifest)?;
+
+        if icons.is_empty() {
+            continue;
+        }
+
+        let (sheets, sprites) = pack(icons);
+        let mut index = AtlasIndex {
+            sheets: Vec::new(),
+            sprites,
+        };
+
+        for (i, sheet) in sheets.iter().enumerate() {
+            let name = format!("{}_{}.png", category.name, i);
+            sheet.save(out_dir.join(&name))?;
+            index.sheets.push(name);
+        }
+
+        println!("Packed {} {} icons into {} sheets", index.sprites.len(), category.name, index.sheets.len());
+
+        let index = json::to_string_pretty(&index)?;
+        fs::write(out_dir.join(format!("{}.json", category.name)), index)?;
+    }
+
+    Ok(())
+}
+
+fn load_icons(category: &Category, data_dir: &Path, image_dir: &Path, manifest: &Manifest) -> Fallible<Vec<Icon>> {
+    let mut icons = Vec::new();
+
+    for item in dataset::load(data_dir, category)? {
+        let (id, image) = match (dataset::id(&item), dataset::image(category, &item, manifest)) {
+            (Some(id), Some(image)) => (id, image),
+            _ => continue,
+        };
+        let path = image_dir.join(image);
+
+        if !path.exists() {
+            eprintln!("Missing icon '{}' of {} #{}", path.display(), category.name, id);
+            continue;
+        }
+
+        let image = image::open(&path)?.to_rgba8();
+
+        if image.width() + 2 * PADDING > MAX_SHEET_SIZE || image.height() + 2 * PADDING > MAX_SHEET_SIZE {
+            eprintln!("Skipping '{}', it does not fit into a sheet", path.display());
+            continue;
+        }
+
+        icons.push(Icon { id, image });
+    }
+
+    Ok(icons)
+}
+
+/// Packs the icons row by row ("shelf packing"), tallest first.
+fn pack(mut icons: Vec<Icon>) -> (Vec<RgbaImage>, BTreeMap<u64, Sprite>) {
+    icons.sort_by_key(|icon| (std::cmp::Reverse(icon.image.height()), icon.id));
+
+    let mut placements = Vec::new();
+    let mut sheet_sizes = vec![(0, 0)];
+    let (mut x, mut y, mut shelf_height) = (0, 0, 0);
+
+    for icon in &icons {
+        let (width, height) = icon.image.dimensions();
+
+        // Sprites are padded on both sides, the sheet edge gets the trailing padding
+        if x + width + 2 * PADDING > MAX_SHEET_SIZE {
+            x = 0;
+            y += shelf_height;
+            shelf_height = 0;
+        }
+
+        if y + height + 2 * PADDING > MAX_SHEET_SIZE {
+            sheet_sizes.push((0, 0));
+            x = 0;
+            y = 0;
+            shelf_height = 0;
+        }
+
+        let sheet = sheet_sizes.len() - 1;
+        placements.push(Sprite { sheet, x: x + PADDING, y: y + PADDING, width, height });
+
+        x += width + PADDING;
+        shelf_height = shelf_height.max(height + PADDING);
+
+        let (sheet_width, sheet_height) = &mut sheet_sizes[sheet];
+        *sheet_width = (*sheet_width).max(x + PADDING);
+        *sheet_height = y + shelf_height + PADDING;
+    }
+
+    let mut sheets = sheet_sizes.iter()
+        .map(|&(width, height)| RgbaImage::new(width, height))
+        .collect::<Vec<_>>();
+    let mut sprites = BTreeMap::new();
+
+    for (icon, sprite) in icons.iter().zip(placements) {
+        imageops::replace(&mut sheets[sprite.sheet], &icon.image, sprite.x, sprite.y);
+        sprites.insert(icon.id, sprite);
+    }
+
+    (sheets, sprites)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sheets_stay_within_the_maximum_size() {
+        let size = (MAX_SHEET_SIZE - PADDING) / 4 - PADDING;
+        let icons = (0..40)
+            .map(|id| Icon { id, image: RgbaImage::new(size, size) })
+            .collect();
+
+        let (sheets, sprites) = pack(icons);
+
+        assert_eq!(sprites.len(), 40);
+        assert_eq!(sheets.len(), 3);
+        assert!(sheets.iter().all(|sheet| sheet.width() <= MAX_SHEET_SIZE && sheet.height() <= MAX_SHEET_SIZE));
+        let full_sheet = 4 * (size + PADDING) + PADDING;
+        assert_eq!(sheets[0].dimensions(), (full_sheet, full_sheet));
+    }
+}
@@ -0,0 +1,207 @@
+use std::path::Path;
+use std::fs;
+use serde_json::Value;
+use failure::{Fallible, bail};
+use itertools::Itertools;
+use crate::dataset;
+use crate::ics::{Calendar, Event};
+use crate::personality::Personality;
+use crate::species::Species;
+
+/// (uid, name, birthday as [day, month])
+const NPC_BIRTHDAYS: &[(&str, &str, [u8; 2])] = &[
+    ("npc-isabelle", "Isabelle", [20, 12]),
+    ("npc-tom-nook", "Tom Nook", [30, 5]),
+    ("npc-timmy-tommy", "Timmy & Tommy", [7, 6]),
+    ("npc-kk-slider", "K.K. Slider", [23, 8]),
+    ("npc-blathers", "Blathers", [24, 2]),
+    ("npc-celeste", "Celeste", [7, 2]),
+    ("npc-mabel", "Mabel", [2, 8]),
+    ("npc-sable", "Sable", [22, 3]),
+    ("npc-label", "Label", [16, 11]),
+    ("npc-daisy-mae", "Daisy Mae", [8, 2]),
+];
+
+/// Seasonal events on fixed dates, as (uid, name, [day, month]).
+/// Events that move every year (e.g. Bunny Day or Fishing Tourneys) are left out.
+const EVENTS: &[(&str, &str, [u8; 2])] = &[
+    ("event-new-years-day", "New Year's Day", [1, 1]),
+    ("event-valentines-day", "Valentine's Day", [14, 2]),
+    ("event-halloween", "Halloween", [31, 10]),
+    ("event-toy-day", "Toy Day", [24, 12]),
+    ("event-countdown", "New Year's Eve Countdown", [31, 12]),
+];
+
+pub struct Options<'a> {
+    /// File listing the villagers to include, one english name, german name or ID per line
+    pub residents: Option<&'a Path>,
+    /// Whether to add NPC birthdays and seasonal events
+    pub events: bool,
+}
+
+/// Writes an iCalendar file with a yearly event per villager birthday.
+pub fn export(data_dir: impl AsRef<Path>, out: impl AsRef<Path>, options: &Options) -> Fallible<()> {
+    let villagers = dataset::load(data_dir, dataset::category("villagers")?)?;
+    let residents = options.residents.map(read_residents).transpose()?;
+    let calendar = calendar(&villagers, residents.as_deref(), options.events)?;
+
+    println!("Writing {} events to '{}'", calendar.events.len(), out.as_ref().display());
+
+    fs::write(out, calendar.render()?)?;
+
+    Ok(())
+}
+
+/// Builds the calendar of the given villagers, only of `residents` if given.
+fn calendar(villagers: &[Value], residents: Option<&[String]>, events: bool) -> Fallible<Calendar> {
+    let mut villagers = villagers.iter().collect_vec();
+
+    if villagers.is_empty() {
+        bail!("No villagers dumped yet");
+    }
+
+    if let Some(residents) = residents {
+        villagers.retain(|villager| residents.iter().any(|resident| is_resident(villager, resident)));
+
+        for resident in residents {
+            if !villagers.iter().any(|villager| is_resident(villager, resident)) {
+                eprintln!("Unknown resident '{}'", resident);
+            }
+        }
+    }
+
+    let mut calendar = Calendar::new("Animal Crossing Birthdays");
+
+    for villager in villagers {
+        let (id, name) = match (dataset::id(villager), dataset::english_name(villager)) {
+            (Some(id), Some(name)) => (id, name),
+            _ => continue,
+        };
+        let birthday = match dataset::birthday(villager) {
+            Some(birthday) => birthday,
+            None => {
+                eprintln!("Villager '{}' has no birthday", name);
+                continue;
+            },
+        };
+        let event = Event::yearly(format!("villager-{}", id), format!("{}'s birthday", name), birthday);
+
+        calendar.events.push(match description(villager) {
+            Some(description) => event.with_description(description),
+            None => event,
+        });
+    }
+
+    if events {
+        for &(uid, name, birthday) in NPC_BIRTHDAYS {
+            calendar.events.push(Event::yearly(uid, format!("{}'s birthday", name), birthday));
+        }
+
+        for &(uid, name, date) in EVENTS {
+            calendar.events.push(Event::yearly(uid, name, date));
+        }
+    }
+
+    Ok(calendar)
+}
+
+fn read_residents(path: &Path) -> Fallible<Vec<String>> {
+    let residents = fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect();
+
+    Ok(residents)
+}
+
+fn is_resident(villager: &Value, resident: &str) -> bool {
+    if dataset::id(villager).map(|id| id.to_string()).as_deref() == Some(resident) {
+        return true;
+    }
+
+    villager.get("name")
+        .and_then(Value::as_object)
+        .map(|names| names.values()
+            .filter_map(Value::as_str)
+            .any(|name| name.to_lowercase() == resident)
+        )
+        .unwrap_or(false)
+}
+
+/// E.g. "Lazy Dog"
+fn description(villager: &Value) -> Option<String> {
+    let personality = villager.get("personality")?.as_str().and_then(Personality::parse)?;
+    let species = villager.get("species")?.as_str().and_then(Species::parse)?;
+
+    Some(format!("{} {}", personality.label("eng")?, species.label("eng")?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn villagers() -> Vec<Value> {
+        vec![
+            json!({ "id": 0, "name": { "eng": "Bob", "deu": "Bob" }, "birthday": [1, 1], "personality": "lazy", "species": "cat" }),
+            json!({ "id": 1, "name": { "eng": "Ankha", "deu": "Ankha" }, "birthday": [22, 9], "personality": "snooty", "species": "cat" }),
+            json!({ "id": 2, "name": { "eng": "Goldie", "deu": "Goldi" }, "birthday": [27, 12], "personality": "normal", "species": "dog" }),
+        ]
+    }
+
+    fn dates(calendar: &Calendar) -> Vec<(String, String)> {
+        let ics = calendar.render().unwrap();
+
+        ics.split("BEGIN:VEVENT").skip(1)
+            .map(|event| {
+                let field = |name: &str| event.lines()
+                    .find_map(|line| line.strip_prefix(name))
+                    .unwrap_or_default()
+                    .to_owned();
+
+                (field("SUMMARY:"), field("DTSTART;VALUE=DATE:"))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn all_villagers() {
+        let calendar = calendar(&villagers(), None, false).unwrap();
+
+        assert_eq!(dates(&calendar), vec![
+            ("Bob's birthday".to_owned(), "20200101".to_owned()),
+            ("Ankha's birthday".to_owned(), "20200922".to_owned()),
+            ("Goldie's birthday".to_owned(), "20201227".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn only_residents() {
+        // By id, english and german name, with an unknown resident in between
+        let residents = ["0".to_owned(), "nobody".to_owned(), "goldi".to_owned()];
+        let calendar = calendar(&villagers(), Some(&residents), false).unwrap();
+
+        assert_eq!(dates(&calendar), vec![
+            ("Bob's birthday".to_owned(), "20200101".to_owned()),
+            ("Goldie's birthday".to_owned(), "20201227".to_owned()),
+        ]);
+    }
+
+    #[test]
+    fn events() {
+        let calendar = calendar(&villagers(), Some(&[]), true).unwrap();
+        let dates = dates(&calendar);
+
+        assert_eq!(dates.len(), NPC_BIRTHDAYS.len() + EVENTS.len());
+        assert!(dates.contains(&("Isabelle's birthday".to_owned(), "20201220".to_owned())));
+        assert!(dates.contains(&("Toy Day".to_owned(), "20201224".to_owned())));
+        assert!(!dates.iter().any(|(summary, _)| summary.contains("April")));
+    }
+
+    #[test]
+    fn no_villagers() {
+        assert!(calendar(&[], None, true).is_err());
+    }
+}
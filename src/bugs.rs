@@ -5,15 +5,27 @@ use serde::*;
 use failure::{Fallible, format_err};
 use itertools::Itertools;
 use crate::common::*;
+use crate::pipeline::{Pipeline, Trim};
 use crate::id;
+use crate::prices::Prices;
+use crate::location::Location;
+use crate::spawn::{self, SpawnCondition};
+use crate::details;
+use crate::rarity::Rarity;
 
 #[derive(Debug, Serialize)]
 pub struct Bug {
     pub id: usize,
     #[serde(rename="name")]
     pub names: BTreeMap<String, String>,
-    pub price: i32,
-    pub location: String,
+    pub prices: Prices,
+    pub location: Vec<Location>,
+    pub spawn_conditions: Vec<SpawnCondition>,
+    #[serde(rename="museum_description")]
+    pub museum_descriptions: BTreeMap<String, String>,
+    #[serde(rename="catch_phrase")]
+    pub catch_phrases: BTreeMap<String, String>,
+    pub rarity: Option<Rarity>,
     pub time: Vec<[u8; 2]>,
     #[serde(rename="months_north")]
     pub north_months: Vec<bool>,
@@ -39,6 +51,31 @@ pub fn fetch_all() -> Fallible<Vec<Bug>> {
         }
     }
 
+    let details = details::fetch_all(bugs.iter().map(|bug| &bug.names));
+
+    for bug in &mut bugs {
+        let pages = details.get(&bug.names["en"].to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let details = pages.iter().find(|page| page.language == "en");
+        bug.spawn_conditions = spawn::conditions(&bug.location, details);
+
+        for page in pages {
+            if let Some(description) = page.museum_description() {
+                bug.museum_descriptions.insert(page.language.into(), description);
+            }
+
+            if let Some(phrase) = page.catch_phrase() {
+                bug.catch_phrases.insert(page.language.into(), phrase);
+            }
+        }
+
+        bug.rarity = details.and_then(|details| details.rarity());
+
+        let special_price = details.and_then(|details| details.price_at(&["flick"]));
+        bug.prices = bug.prices.with_special_buyer(special_price);
+    }
+
     Ok(bugs)
 }
 
@@ -80,12 +117,11 @@ fn parse_bugs(page: Document) -> Fallible<Vec<Bug>> {
             );
 
         let price = north_cols.get(2)
-            .and_then(|price| parse_price(price.text()))
-            .unwrap_or(-1);
+            .and_then(|price| parse_price(price.text()));
         
         let location = north_cols.get(3)
-            .and_then(|location| parse_text(location.text()))
-            .unwrap_or_else(|| "???".into());
+            .map(|location| Location::parse_list(location.text()))
+            .unwrap_or_else(Vec::new);
         
         let time = north_cols.get(4)
             .and_then(|time| parse_time_slots(time.text()))
@@ -113,8 +149,12 @@ fn parse_bugs(page: Document) -> Fallible<Vec<Bug>> {
             id: id::bug(&names["en"]),
             image_url,
             names,
-            price,
+            prices: Prices::sell(price),
             location,
+            spawn_conditions: Vec::new(),
+            museum_descriptions: BTreeMap::new(),
+            catch_phrases: BTreeMap::new(),
+            rarity: None,
             north_months,
             south_months,
             time,
@@ -133,17 +173,21 @@ impl HasFiles for Bug {
 
         if let Some(image_url) = &self.image_url {
             files.push(File {
-                name: format!("i{}.png", self.id),
+                name: format!("i{}", self.id),
+                id: self.id,
+                category: "bugs",
                 url: image_url.clone(),
-                transform: convert_image_to_png,
+                pipeline: Pipeline::default().with_trim(Trim::default()),
             })
         }
 
         if let Some(hi_res_image_url) = &self.hi_res_image_url {
             files.push(File {
-                name: format!("i{}_hi.png", self.id),
+                name: format!("i{}_hi", self.id),
+                id: self.id,
+                category: "bugs",
                 url: hi_res_image_url.clone(),
-                transform: convert_image_to_png,
+                pipeline: Pipeline::default().with_trim(Trim::default()),
             })
         }
 
@@ -1,11 +1,18 @@
 use select::document::Document;
-use failure::{Fallible, bail};
+use failure::{Fallible, bail, format_err};
 use std::path::Path;
 use std::fs;
+use std::sync::{Arc, mpsc};
+use std::thread;
+use std::time::Duration;
+use std::ops::Range;
 use ::reqwest::blocking as reqwest;
+use ::reqwest::StatusCode;
+use ::reqwest::header::{self, HeaderMap};
 use regex::Regex;
-use image::{GenericImageView, ImageFormat, imageops::FilterType};
 use threadpool::ThreadPool;
+use crate::pipeline::{Pipeline, Overrides};
+use crate::manifest::{self, Manifest};
 
 pub fn parse_text(name: impl AsRef<str>) -> Option<String> {
     let name = name
@@ -80,61 +87,316 @@ fn parse_am_pm_time(time: &str) -> Option<u8> {
     })
 }
 
+/// Lowercase words of `text`, e.g. `["river", "clifftop"]` for "River (Clifftop)".
+pub fn words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Position of the first occurrence of the words of `keyword` in `words`.
+/// Words may be in plural, so "rock" matches "rocks", and punctuation is ignored.
+pub fn find_keyword(words: &[String], keyword: &str) -> Option<Range<usize>> {
+    let keyword = self::words(keyword);
+    let matches = |word: &str, expected: &str| {
+        word.strip_prefix(expected)
+            .map(|suffix| matches!(suffix, "" | "s" | "es"))
+            .unwrap_or(false)
+    };
+
+    (0..words.len())
+        .map(|start| start..start + keyword.len())
+        .filter(|range| range.end <= words.len())
+        .find(|range| words[range.clone()].iter().zip(&keyword).all(|(word, expected)| matches(word, expected)))
+}
+
 pub fn download_page(url: &str) -> Fallible<Document> {
-    let page = reqwest::get(url)
-        .unwrap()
-        .text()
-        .unwrap();
+    let page = reqwest::get(url)?
+        .error_for_status()?
+        .text()?;
 
     let page = Document::from(&*page);
 
     Ok(page)
 }
 
+#[derive(Clone)]
+pub struct DownloadOptions {
+    pub threads: usize,
+    /// Number of retries after the first failed attempt
+    pub retries: u32,
+    /// Delay before the first retry, doubled for every further retry
+    pub backoff: Duration,
+    /// Timeout per request
+    pub timeout: Duration,
+    /// Changes applied to the pipeline of every file
+    pub pipeline: Overrides,
+}
+
+impl Default for DownloadOptions {
+    fn default() -> Self {
+        Self {
+            threads: 4,
+            retries: 3,
+            backoff: Duration::from_secs(1),
+            timeout: Duration::from_secs(30),
+            pipeline: Overrides::default(),
+        }
+    }
+}
+
+enum DownloadOutcome {
+    Downloaded,
+    Cached,
+    /// Cached, but upstream changed and the file was downloaded again
+    Updated,
+}
+
 pub fn download_images<T: HasFiles>(items: impl IntoIterator<Item = T>, dir: impl AsRef<Path>) -> Fallible<()> {
-    let tasks = ThreadPool::new(4);
+    download_images_with(items, dir, DownloadOptions::default())
+}
+
+/// Downloads all files of `items` into `dir`.
+/// Failed files don't abort the batch; they are reported once all other files are done.
+pub fn download_images_with<T: HasFiles>(
+    items: impl IntoIterator<Item = T>,
+    dir: impl AsRef<Path>,
+    options: DownloadOptions,
+) -> Fallible<()> {
+    let tasks = ThreadPool::new(options.threads);
     let dir = dir.as_ref();
+    let client = reqwest::Client::builder()
+        .timeout(options.timeout)
+        .build()?;
+    let options = Arc::new(options);
+    let (results_tx, results_rx) = mpsc::channel();
 
     fs::create_dir_all(dir)?;
 
+    let mut manifest = Manifest::load(dir)?;
+
     for item in items {
         for file in item.files() {
-            if tasks.panic_count() > 0 {
-                bail!("Failed to download some files");
-            }
-
             let dir = dir.to_owned();
+            let client = client.clone();
+            let options = options.clone();
+            let results_tx = results_tx.clone();
+            let previous = manifest.entry(&file.name, &file.url).cloned();
 
             tasks.execute(move || {
-                let path = dir.join(file.name);
+                let result = download_file(&client, &file, &dir, &options, previous.as_ref());
+                results_tx.send((file.name, result)).ok();
+            });
+        }
+    }
 
-                if path.exists() {
-                    // println!("Already cached: '{}' to '{}'", file.url, path.display());
-                    return;
-                }
+    drop(results_tx);
+    tasks.join();
 
-                println!("Downloading '{}' to '{}'", file.url, path.display());
+    let (mut downloaded, mut cached, mut updated) = (0, 0, 0);
+    let mut failed = Vec::new();
 
-                let bytes = reqwest::get(&file.url).unwrap()
-                    .error_for_status().unwrap()
-                    .bytes().unwrap()
-                    .to_vec();
-                let bytes = (file.transform)(bytes);
+    for (name, result) in results_rx {
+        match result {
+            Ok((outcome, entry)) => {
+                match outcome {
+                    DownloadOutcome::Downloaded => downloaded += 1,
+                    DownloadOutcome::Cached => cached += 1,
+                    DownloadOutcome::Updated => updated += 1,
+                }
 
-                fs::write(path, bytes).unwrap();
-            });
+                manifest.update(name, entry);
+            },
+            Err(err) => failed.push((name, err)),
         }
     }
 
-    tasks.join();
+    manifest.save(dir)?;
+
+    println!(
+        "Downloaded {} files, {} already cached, {} updated upstream, {} failed",
+        downloaded, cached, updated, failed.len(),
+    );
+
+    for (name, err) in &failed {
+        println!("    {}: {}", name, err);
+    }
 
-    if tasks.panic_count() > 0 {
-        bail!("Failed to download some files");
+    if !failed.is_empty() || tasks.panic_count() > 0 {
+        bail!("Failed to download {} files", failed.len() + tasks.panic_count());
     }
 
     Ok(())
 }
 
+/// Downloads and processes `file`, unless all its outputs are cached.
+/// Cached files are revalidated against the validators of their `previous` manifest entry,
+/// and files without validators yet only get them looked up.
+fn download_file(
+    client: &reqwest::Client,
+    file: &File,
+    dir: &Path,
+    options: &DownloadOptions,
+    previous: Option<&manifest::Entry>,
+) -> Fallible<(DownloadOutcome, manifest::Entry)> {
+    let pipeline = file.pipeline.clone().with_overrides(&options.pipeline);
+    let mut entry = manifest::Entry {
+        id: file.id,
+        category: file.category.into(),
+        url: file.url.clone(),
+        source_sha256: None,
+        etag: None,
+        last_modified: None,
+        outputs: Vec::new(),
+    };
+
+    let cached_outputs = pipeline.variants.iter()
+        .map(|variant| {
+            let path = variant.file_name(&file.name);
+            let data = fs::read(dir.join(&path)).ok()?;
+            let (width, height) = variant.format.validate(&data).ok()?;
+
+            Some(manifest::Output {
+                path,
+                width,
+                height,
+                bytes: data.len() as u64,
+                sha256: manifest::sha256(&data),
+            })
+        })
+        .collect::<Option<Vec<_>>>();
+
+    let mut outcome = DownloadOutcome::Downloaded;
+
+    let bytes = match (cached_outputs, previous) {
+        (Some(outputs), Some(previous)) if previous.etag.is_some() || previous.last_modified.is_some() => {
+            let mut request = client.get(&file.url);
+
+            if let Some(etag) = &previous.etag {
+                request = request.header(header::IF_NONE_MATCH, etag.as_str());
+            }
+
+            if let Some(last_modified) = &previous.last_modified {
+                request = request.header(header::IF_MODIFIED_SINCE, last_modified.as_str());
+            }
+
+            let (status, headers, bytes) = match send_with_retries(request, &file.url, options) {
+                Ok(response) => response,
+                Err(err) => {
+                    eprintln!("Could not revalidate '{}', keeping the cached file: {}", file.url, err);
+                    entry.outputs = outputs;
+                    return Ok((DownloadOutcome::Cached, entry));
+                },
+            };
+
+            if status == StatusCode::NOT_MODIFIED {
+                entry.outputs = outputs;
+                return Ok((DownloadOutcome::Cached, entry));
+            }
+
+            set_validators(&mut entry, &headers);
+
+            if previous.source_sha256.as_deref() == Some(&manifest::sha256(&bytes)) {
+                entry.outputs = outputs;
+                return Ok((DownloadOutcome::Cached, entry));
+            }
+
+            outcome = DownloadOutcome::Updated;
+
+            bytes
+        },
+        (Some(outputs), _) => {
+            // Only looks up the validators for the next run, a single attempt is enough
+            match client.head(&file.url).send().and_then(|response| response.error_for_status()) {
+                Ok(response) => set_validators(&mut entry, response.headers()),
+                Err(err) => eprintln!("Could not look up validators of '{}': {}", file.url, err),
+            }
+
+            entry.outputs = outputs;
+
+            return Ok((DownloadOutcome::Cached, entry));
+        },
+        (None, _) => {
+            println!("Downloading '{}' as '{}'", file.url, file.name);
+
+            let (_, headers, bytes) = send_with_retries(client.get(&file.url), &file.url, options)?;
+            set_validators(&mut entry, &headers);
+
+            bytes
+        },
+    };
+
+    entry.source_sha256 = Some(manifest::sha256(&bytes));
+
+    let outputs = pipeline.process(&bytes)
+        .map_err(|err| format_err!("Invalid image from '{}': {}", file.url, err))?;
+
+    for (variant, bytes) in outputs {
+        let path = variant.file_name(&file.name);
+        let (width, height) = variant.format.validate(&bytes)?;
+
+        entry.outputs.push(manifest::Output {
+            path: path.clone(),
+            width,
+            height,
+            bytes: bytes.len() as u64,
+            sha256: manifest::sha256(&bytes),
+        });
+
+        let path = dir.join(path);
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        write_atomically(path, bytes)?;
+    }
+
+    Ok((outcome, entry))
+}
+
+/// Sends `request` and reads the response, retrying failed attempts with exponential backoff.
+fn send_with_retries(
+    request: reqwest::RequestBuilder,
+    url: &str,
+    options: &DownloadOptions,
+) -> Fallible<(StatusCode, HeaderMap, Vec<u8>)> {
+    let mut backoff = options.backoff;
+    let mut attempt = 0;
+
+    loop {
+        let attempt_request = request.try_clone()
+            .ok_or_else(|| format_err!("Request to '{}' can't be retried", url))?;
+        let response = attempt_request.send()
+            .and_then(|response| response.error_for_status())
+            .and_then(|response| {
+                let (status, headers) = (response.status(), response.headers().clone());
+                Ok((status, headers, response.bytes()?.to_vec()))
+            });
+
+        match response {
+            Ok(response) => return Ok(response),
+            Err(err) if attempt < options.retries => {
+                attempt += 1;
+                println!("Retrying '{}' in {:?} ({}/{}): {}", url, backoff, attempt, options.retries, err);
+                thread::sleep(backoff);
+                backoff *= 2;
+            },
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+fn set_validators(entry: &mut manifest::Entry, headers: &HeaderMap) {
+    let value = |name| headers.get(name)
+        .and_then(|value| value.to_str().ok())
+        .map(<_>::into);
+
+    entry.etag = value(header::ETAG);
+    entry.last_modified = value(header::LAST_MODIFIED);
+}
+
 pub fn tweak_image_url(url: impl AsRef<str>) -> String {
     let url = url.as_ref();
     let re = Regex::new(r"(/scale-to-width-down/\d+)|(w=\d+)").unwrap();
@@ -148,9 +410,12 @@ pub trait HasFiles {
 }
 
 pub struct File {
+    /// File name without extension, completed by the pipeline variants
     pub name: String,
+    pub id: usize,
+    pub category: &'static str,
     pub url: String,
-    pub transform: fn(Vec<u8>) -> Vec<u8>,
+    pub pipeline: Pipeline,
 }
 
 impl<T: HasFiles> HasFiles for &'_ T {
@@ -159,18 +424,21 @@ impl<T: HasFiles> HasFiles for &'_ T {
     }
 }
 
-pub fn convert_image_to_png(source: Vec<u8>) -> Vec<u8> {
-    let mut source = image::load_from_memory(&source).unwrap();
-    let target_width = 256;
-    let target_height = 256;
-    let target_dimensions = (target_width, target_height);
+/// Writes `contents` to a temporary file next to `path` and renames it into place,
+/// so readers never observe a partially written file.
+pub fn write_atomically(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> Fallible<()> {
+    let path = path.as_ref();
+    let file_name = path.file_name()
+        .ok_or_else(|| format_err!("Invalid path '{}'", path.display()))?
+        .to_string_lossy();
+    let tmp_path = path.with_file_name(format!(".{}.tmp", file_name));
 
-    if source.dimensions() != target_dimensions {
-        source = source.resize(target_width, target_height, FilterType::Lanczos3);
-    }
+    fs::write(&tmp_path, contents)?;
 
-    let mut target = Vec::new();
-    source.write_to(&mut target, ImageFormat::Png).unwrap();
+    if let Err(err) = fs::rename(&tmp_path, path) {
+        fs::remove_file(&tmp_path).ok();
+        return Err(err.into());
+    }
 
-    target
+    Ok(())
 }
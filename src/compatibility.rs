@@ -0,0 +1,360 @@
+use std::path::Path;
+use serde::*;
+use serde_json::Value;
+use failure::{Fallible, bail, format_err};
+use itertools::Itertools;
+use crate::dataset;
+use crate::personality::Personality;
+use crate::species::Species;
+
+/// Number of best and worst pairs printed by `report`
+const REPORTED_PAIRS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all="lowercase")]
+pub enum Zodiac {
+    Aries,
+    Taurus,
+    Gemini,
+    Cancer,
+    Leo,
+    Virgo,
+    Libra,
+    Scorpio,
+    Sagittarius,
+    Capricorn,
+    Aquarius,
+    Pisces,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Element {
+    Fire,
+    Earth,
+    Air,
+    Water,
+}
+
+/// (sign, first day as [day, month])
+const ZODIAC_STARTS: &[(Zodiac, [u8; 2])] = &[
+    (Zodiac::Capricorn, [22, 12]),
+    (Zodiac::Sagittarius, [23, 11]),
+    (Zodiac::Scorpio, [24, 10]),
+    (Zodiac::Libra, [23, 9]),
+    (Zodiac::Virgo, [23, 8]),
+    (Zodiac::Leo, [23, 7]),
+    (Zodiac::Cancer, [22, 6]),
+    (Zodiac::Gemini, [21, 5]),
+    (Zodiac::Taurus, [20, 4]),
+    (Zodiac::Aries, [21, 3]),
+    (Zodiac::Pisces, [19, 2]),
+    (Zodiac::Aquarius, [20, 1]),
+];
+
+impl Zodiac {
+    /// Derives the star sign from a `[day, month]` birthday.
+    pub fn from_birthday([day, month]: [u8; 2]) -> Option<Self> {
+        if !(1..=31).contains(&day) || !(1..=12).contains(&month) {
+            return None;
+        }
+
+        let sign = ZODIAC_STARTS.iter()
+            .find(|&&(_, [start_day, start_month])| (month, day) >= (start_month, start_day))
+            .map(|&(sign, _)| sign)
+            // Early january belongs to the capricorn of the previous year
+            .unwrap_or(Zodiac::Capricorn);
+
+        Some(sign)
+    }
+
+    pub fn element(self) -> Element {
+        match self {
+            Zodiac::Aries | Zodiac::Leo | Zodiac::Sagittarius => Element::Fire,
+            Zodiac::Taurus | Zodiac::Virgo | Zodiac::Capricorn => Element::Earth,
+            Zodiac::Gemini | Zodiac::Libra | Zodiac::Aquarius => Element::Air,
+            Zodiac::Cancer | Zodiac::Scorpio | Zodiac::Pisces => Element::Water,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all="lowercase")]
+pub enum Rating {
+    Bad,
+    Neutral,
+    Good,
+}
+
+impl Rating {
+    pub fn score(self) -> u32 {
+        match self {
+            Rating::Bad => 0,
+            Rating::Neutral => 1,
+            Rating::Good => 2,
+        }
+    }
+
+    pub fn symbol(self) -> char {
+        match self {
+            Rating::Bad => '✖',
+            Rating::Neutral => '◆',
+            Rating::Good => '♥',
+        }
+    }
+}
+
+/// The parts of a villager that matter for compatibility.
+#[derive(Debug, Clone, Serialize)]
+pub struct Profile {
+    pub id: u64,
+    pub name: String,
+    pub personality: Option<Personality>,
+    pub species: Option<Species>,
+    pub sign: Option<Zodiac>,
+}
+
+impl Profile {
+    /// Reads a profile from a dumped villager.
+    pub fn from_value(villager: &Value) -> Option<Self> {
+        Some(Profile {
+            id: dataset::id(villager)?,
+            name: dataset::english_name(villager)?.into(),
+            personality: villager.get("personality").and_then(Value::as_str).and_then(Personality::parse),
+            species: villager.get("species").and_then(Value::as_str).and_then(Species::parse),
+            sign: dataset::birthday(villager).and_then(Zodiac::from_birthday),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct Compatibility {
+    pub personality: Rating,
+    pub species: Rating,
+    pub sign: Rating,
+}
+
+impl Compatibility {
+    /// Sum of all ratings, from 0 (worst) to 6 (best)
+    pub fn score(&self) -> u32 {
+        self.personality.score() + self.species.score() + self.sign.score()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Pair<'a> {
+    pub a: &'a Profile,
+    pub b: &'a Profile,
+    pub compatibility: Compatibility,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Group<'a> {
+    /// Pairs sorted from best to worst
+    pub pairs: Vec<Pair<'a>>,
+    /// Average pair score
+    pub score: f32,
+}
+
+/// Rates a pair of villagers. Unknown attributes are rated neutral.
+pub fn rate(a: &Profile, b: &Profile) -> Compatibility {
+    Compatibility {
+        personality: both(a.personality, b.personality, rate_personalities),
+        species: both(a.species, b.species, rate_species),
+        sign: both(a.sign, b.sign, rate_signs),
+    }
+}
+
+/// Rates every pair of a group of villagers.
+pub fn rate_group(villagers: &[Profile]) -> Group<'_> {
+    let pairs = villagers.iter()
+        .tuple_combinations()
+        .map(|(a, b)| Pair { a, b, compatibility: rate(a, b) })
+        .sorted_by_key(|pair| std::cmp::Reverse(pair.compatibility.score()))
+        .collect_vec();
+
+    let score = if pairs.is_empty() {
+        0.
+    } else {
+        pairs.iter().map(|pair| pair.compatibility.score()).sum::<u32>() as f32 / pairs.len() as f32
+    };
+
+    Group { pairs, score }
+}
+
+/// Prints the best and worst pairs of the given villagers.
+pub fn report(data_dir: impl AsRef<Path>, ids: &[u64]) -> Fallible<()> {
+    let villagers = dataset::load(data_dir, dataset::category("villagers")?)?;
+
+    if villagers.is_empty() {
+        bail!("No villagers dumped yet");
+    }
+
+    let profiles = ids.iter()
+        .map(|&id| villagers.iter()
+            .find(|villager| dataset::id(villager) == Some(id))
+            .and_then(Profile::from_value)
+            .ok_or_else(|| format_err!("Unknown villager id {}", id))
+        )
+        .collect::<Fallible<Vec<_>>>()?;
+
+    if profiles.len() < 2 {
+        bail!("At least two villagers are needed");
+    }
+
+    let group = rate_group(&profiles);
+    // Small groups split their pairs, so no pair shows up as both best and worst
+    let worst_count = REPORTED_PAIRS.min(group.pairs.len() / 2);
+    let best = group.pairs.iter().take(REPORTED_PAIRS.min(group.pairs.len() - worst_count));
+    let worst = group.pairs.iter().rev().take(worst_count).collect_vec();
+
+    println!("Group score: {:.2} / 6", group.score);
+    println!();
+    println!("Best pairs:");
+    for pair in best {
+        print_pair(pair);
+    }
+
+    if !worst.is_empty() {
+        println!();
+        println!("Worst pairs:");
+        for pair in worst {
+            print_pair(pair);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_pair(pair: &Pair) {
+    let compatibility = &pair.compatibility;
+
+    println!(
+        "    {} & {}: {} (personality {}, species {}, star sign {})",
+        pair.a.name,
+        pair.b.name,
+        compatibility.score(),
+        compatibility.personality.symbol(),
+        compatibility.species.symbol(),
+        compatibility.sign.symbol(),
+    );
+}
+
+fn both<T: Copy>(a: Option<T>, b: Option<T>, rate: fn(T, T) -> Rating) -> Rating {
+    match (a, b) {
+        (Some(a), Some(b)) => rate(a, b),
+        _ => Rating::Neutral,
+    }
+}
+
+fn rate_personalities(a: Personality, b: Personality) -> Rating {
+    use Personality::*;
+
+    let pair = if a <= b { (a, b) } else { (b, a) };
+
+    match pair {
+        | (Normal, Normal)
+        | (Normal, Lazy)
+        | (Normal, Cranky)
+        | (Normal, Smug)
+        | (Peppy, Peppy)
+        | (Peppy, Lazy)
+        | (Peppy, Jock)
+        | (Snooty, Snooty)
+        | (Snooty, Cranky)
+        | (Snooty, Smug)
+        | (Sisterly, Sisterly)
+        | (Sisterly, Lazy)
+        | (Sisterly, Jock)
+        | (Lazy, Lazy)
+        | (Jock, Jock)
+        | (Cranky, Cranky)
+        | (Smug, Smug)
+        => Rating::Good,
+
+        | (Normal, Jock)
+        | (Peppy, Cranky)
+        | (Snooty, Sisterly)
+        | (Snooty, Lazy)
+        | (Lazy, Jock)
+        | (Jock, Cranky)
+        => Rating::Bad,
+
+        _ => Rating::Neutral,
+    }
+}
+
+/// Species pairs that get along particularly well or badly
+const SPECIES_RATINGS: &[(Species, Species, Rating)] = &[
+    (Species::Bear, Species::Cub, Rating::Good),
+    (Species::Bull, Species::Cow, Rating::Good),
+    (Species::Cat, Species::Lion, Rating::Good),
+    (Species::Cat, Species::Tiger, Rating::Good),
+    (Species::Dog, Species::Wolf, Rating::Good),
+    (Species::Goat, Species::Sheep, Rating::Good),
+    (Species::Hamster, Species::Mouse, Rating::Good),
+    (Species::Hamster, Species::Squirrel, Rating::Good),
+    (Species::Kangaroo, Species::Koala, Rating::Good),
+    (Species::Bird, Species::Eagle, Rating::Good),
+    (Species::Chicken, Species::Duck, Rating::Good),
+    (Species::Cat, Species::Mouse, Rating::Bad),
+    (Species::Cat, Species::Bird, Rating::Bad),
+    (Species::Cat, Species::Dog, Rating::Bad),
+    (Species::Eagle, Species::Mouse, Rating::Bad),
+    (Species::Lion, Species::Deer, Rating::Bad),
+    (Species::Tiger, Species::Deer, Rating::Bad),
+    (Species::Wolf, Species::Sheep, Rating::Bad),
+    (Species::Wolf, Species::Goat, Rating::Bad),
+    (Species::Wolf, Species::Rabbit, Rating::Bad),
+];
+
+fn rate_species(a: Species, b: Species) -> Rating {
+    if a == b {
+        return Rating::Good;
+    }
+
+    SPECIES_RATINGS.iter()
+        .find(|&&(x, y, _)| (x, y) == (a, b) || (x, y) == (b, a))
+        .map(|&(_, _, rating)| rating)
+        .unwrap_or(Rating::Neutral)
+}
+
+fn rate_signs(a: Zodiac, b: Zodiac) -> Rating {
+    use Element::*;
+
+    match (a.element(), b.element()) {
+        (a, b) if a == b => Rating::Good,
+        | (Fire, Air) | (Air, Fire)
+        | (Earth, Water) | (Water, Earth)
+        => Rating::Neutral,
+        _ => Rating::Bad,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sign_from_birthday() {
+        assert_eq!(Zodiac::from_birthday([21, 3]), Some(Zodiac::Aries));
+        assert_eq!(Zodiac::from_birthday([20, 3]), Some(Zodiac::Pisces));
+        assert_eq!(Zodiac::from_birthday([22, 6]), Some(Zodiac::Cancer));
+        assert_eq!(Zodiac::from_birthday([21, 12]), Some(Zodiac::Sagittarius));
+    }
+
+    #[test]
+    fn capricorn_wraps_around_new_year() {
+        assert_eq!(Zodiac::from_birthday([22, 12]), Some(Zodiac::Capricorn));
+        assert_eq!(Zodiac::from_birthday([31, 12]), Some(Zodiac::Capricorn));
+        assert_eq!(Zodiac::from_birthday([1, 1]), Some(Zodiac::Capricorn));
+        assert_eq!(Zodiac::from_birthday([19, 1]), Some(Zodiac::Capricorn));
+        assert_eq!(Zodiac::from_birthday([20, 1]), Some(Zodiac::Aquarius));
+    }
+
+    #[test]
+    fn invalid_birthdays() {
+        assert_eq!(Zodiac::from_birthday([0, 1]), None);
+        assert_eq!(Zodiac::from_birthday([32, 1]), None);
+        assert_eq!(Zodiac::from_birthday([1, 13]), None);
+    }
+}
@@ -0,0 +1,265 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::fs;
+use serde_json::Value;
+use failure::{Fallible, bail, format_err};
+use itertools::Itertools;
+use crate::dataset;
+use crate::ics::{Calendar, Event};
+use crate::spawn::SpawnCondition;
+
+/// Categories with seasonal availability
+const CRITTER_CATEGORIES: &[&str] = &["bugs", "fish"];
+
+const MONTHS: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hemisphere {
+    North,
+    South,
+}
+
+impl Hemisphere {
+    pub fn parse(text: &str) -> Fallible<Self> {
+        match &*text.to_lowercase() {
+            "north" | "n" => Ok(Hemisphere::North),
+            "south" | "s" => Ok(Hemisphere::South),
+            _ => Err(format_err!("Unknown hemisphere '{}'", text)),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Hemisphere::North => "Northern",
+            Hemisphere::South => "Southern",
+        }
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            Hemisphere::North => "north",
+            Hemisphere::South => "south",
+        }
+    }
+
+    fn months_field(self) -> &'static str {
+        match self {
+            Hemisphere::North => "months_north",
+            Hemisphere::South => "months_south",
+        }
+    }
+}
+
+pub struct Options {
+    pub hemisphere: Hemisphere,
+    /// Critters to leave out, as (category, id)
+    pub exclude: BTreeSet<(&'static str, u64)>,
+}
+
+/// Writes an iCalendar file with the arrival and departure of each bug and fish,
+/// and the daily time windows of critters that are not around all day.
+/// Events of critters that depend on the weather note so in their description.
+pub fn export(data_dir: impl AsRef<Path>, out: impl AsRef<Path>, options: &Options) -> Fallible<()> {
+    let data_dir = data_dir.as_ref();
+    let hemisphere = options.hemisphere;
+    let mut calendar = Calendar::new(format!("Animal Crossing Critters ({} Hemisphere)", hemisphere.name()));
+
+    for &name in CRITTER_CATEGORIES {
+        let category = dataset::category(name)?;
+
+        for critter in dataset::load(data_dir, category)? {
+            let (id, critter_name) = match (dataset::id(&critter), dataset::english_name(&critter)) {
+                (Some(id), Some(critter_name)) => (id, critter_name),
+                _ => continue,
+            };
+
+            if options.exclude.contains(&(category.name, id)) {
+                continue;
+            }
+
+            let months = months(&critter, hemisphere);
+            let uid = format!("{}-{}-{}", category.name, id, hemisphere.code());
+            let weather_notes = weather_notes(&critter);
+            let with_notes = |description: String| {
+                std::iter::once(description.as_str())
+                    .chain(weather_notes.iter().copied())
+                    .join("\n")
+            };
+
+            for [first, last] in seasons(&months) {
+                calendar.events.push(
+                    Event::yearly(format!("{}-arrives-{}", uid, first), format!("{} arrives", critter_name), [1, first])
+                        .with_description(with_notes(format!("Available until the end of {}", MONTHS[last as usize - 1])))
+                );
+                calendar.events.push(
+                    Event::yearly(format!("{}-leaves-{}", uid, last), format!("{} leaves after this month", critter_name), [1, last])
+                        .with_description(with_notes(format!("Available since {}", MONTHS[first as usize - 1])))
+                );
+            }
+
+            let available_months = (1..=12).filter(|&month| months[month as usize - 1]).collect_vec();
+            let start_month = match seasons(&months).first() {
+                Some(&[first, _]) => first,
+                None if !available_months.is_empty() => 1,
+                None => continue,
+            };
+
+            for [start, end] in time_windows(&critter) {
+                if [start, end] == [0, 24] {
+                    continue;
+                }
+
+                let mut event = Event::daily(
+                    format!("{}-window-{}-{}", uid, start, end),
+                    critter_name,
+                    [start, end],
+                    start_month,
+                    &available_months,
+                );
+
+                if !weather_notes.is_empty() {
+                    event = event.with_description(weather_notes.join("\n"));
+                }
+
+                calendar.events.push(event);
+            }
+        }
+    }
+
+    if calendar.events.is_empty() {
+        bail!("No bugs or fish dumped yet");
+    }
+
+    println!("Writing {} events to '{}'", calendar.events.len(), out.as_ref().display());
+
+    fs::write(out, calendar.render()?)?;
+
+    Ok(())
+}
+
+/// Reads a list of critters, one english name per line.
+pub fn read_critter_list(data_dir: impl AsRef<Path>, path: impl AsRef<Path>) -> Fallible<BTreeSet<(&'static str, u64)>> {
+    let names = fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_lowercase)
+        .collect::<BTreeSet<_>>();
+    let mut critters = BTreeSet::new();
+
+    for &name in CRITTER_CATEGORIES {
+        let category = dataset::category(name)?;
+
+        for critter in dataset::load(data_dir.as_ref(), category)? {
+            let is_listed = dataset::english_name(&critter)
+                .map(|name| names.contains(&name.to_lowercase()))
+                .unwrap_or(false);
+
+            if let (true, Some(id)) = (is_listed, dataset::id(&critter)) {
+                critters.insert((category.name, id));
+            }
+        }
+    }
+
+    Ok(critters)
+}
+
+fn months(critter: &Value, hemisphere: Hemisphere) -> [bool; 12] {
+    let mut months = [false; 12];
+    let values = critter.get(hemisphere.months_field()).and_then(Value::as_array);
+
+    for (month, value) in months.iter_mut().zip(values.into_iter().flatten()) {
+        *month = value.as_bool().unwrap_or(false);
+    }
+
+    months
+}
+
+/// Returns the first and last month (1-12) of each uninterrupted season.
+/// Seasons may wrap around the end of the year, critters around all year have none.
+fn seasons(months: &[bool; 12]) -> Vec<[u8; 2]> {
+    let mut seasons = Vec::new();
+
+    if months.iter().all(|&available| available) {
+        return seasons;
+    }
+
+    for start in 0..12 {
+        if !months[start] || months[(start + 11) % 12] {
+            continue;
+        }
+
+        let mut end = start;
+
+        while months[(end + 1) % 12] {
+            end = (end + 1) % 12;
+        }
+
+        seasons.push([start as u8 + 1, end as u8 + 1]);
+    }
+
+    seasons
+}
+
+fn weather_notes(critter: &Value) -> Vec<&'static str> {
+    critter.get("spawn_conditions")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|condition| serde_json::from_value::<SpawnCondition>(condition.clone()).ok())
+        .filter_map(SpawnCondition::weather_note)
+        .collect()
+}
+
+fn time_windows(critter: &Value) -> Vec<[u8; 2]> {
+    critter.get("time")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(|window| match window.as_array()?.as_slice() {
+            [start, end] => Some([start.as_u64()? as u8, end.as_u64()? as u8]),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn available(months: &[u8]) -> [bool; 12] {
+        let mut available = [false; 12];
+
+        for &month in months {
+            available[month as usize - 1] = true;
+        }
+
+        available
+    }
+
+    #[test]
+    fn single_season() {
+        assert_eq!(seasons(&available(&[3, 4, 5])), vec![[3, 5]]);
+        assert_eq!(seasons(&available(&[8])), vec![[8, 8]]);
+    }
+
+    #[test]
+    fn season_wraps_from_december_to_january() {
+        assert_eq!(seasons(&available(&[11, 12, 1, 2])), vec![[11, 2]]);
+        assert_eq!(seasons(&available(&[12, 1])), vec![[12, 1]]);
+    }
+
+    #[test]
+    fn several_seasons() {
+        assert_eq!(seasons(&available(&[1, 2, 6, 7, 12])), vec![[6, 7], [12, 2]]);
+    }
+
+    #[test]
+    fn all_year_and_never_have_no_seasons() {
+        assert!(seasons(&[true; 12]).is_empty());
+        assert!(seasons(&[false; 12]).is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+use std::path::Path;
+use std::fs;
+use serde_json::{self as json, Value};
+use failure::{Fallible, format_err};
+use crate::manifest::Manifest;
+
+pub struct Category {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub data_file: &'static str,
+    /// Image file name without extension, as recorded in the image manifest
+    pub image_name: fn(&Value) -> Option<String>,
+}
+
+pub const CATEGORIES: &[Category] = &[
+    Category {
+        name: "bugs",
+        title: "Bugs",
+        data_file: "insects.json",
+        image_name: |item| Some(format!("i{}", id(item)?)),
+    },
+    Category {
+        name: "fish",
+        title: "Fish",
+        data_file: "fish.json",
+        image_name: |item| Some(format!("f{}", id(item)?)),
+    },
+    Category {
+        name: "fossils",
+        title: "Fossils",
+        data_file: "fossils.json",
+        image_name: |item| Some(format!("fo{}", id(item)?)),
+    },
+    Category {
+        name: "flowers",
+        title: "Flowers",
+        data_file: "flowers.json",
+        image_name: |item| Some(format!("fl{}", id(item)?)),
+    },
+    Category {
+        name: "art",
+        title: "Art",
+        data_file: "art.json",
+        image_name: |item| Some(format!("art{}", id(item)?)),
+    },
+    Category {
+        name: "villagers",
+        title: "Villagers",
+        data_file: "villagers.json",
+        image_name: |item| Some(format!("villagerdb/{}", english_name(item)?.to_lowercase())),
+    },
+];
+
+pub fn category(name: &str) -> Fallible<&'static Category> {
+    CATEGORIES.iter()
+        .find(|category| category.name == name)
+        .ok_or_else(|| format_err!("Unknown category '{}'", name))
+}
+
+/// Loads the dumped items of a category.
+/// Returns an empty list if the category has not been dumped yet.
+pub fn load(data_dir: impl AsRef<Path>, category: &Category) -> Fallible<Vec<Value>> {
+    let path = data_dir.as_ref().join(category.data_file);
+
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let data = fs::read(&path)?;
+    let items = json::from_slice(&data)
+        .map_err(|err| format_err!("Failed to parse '{}': {}", path.display(), err))?;
+
+    Ok(items)
+}
+
+/// Path of the item's image inside the image folder.
+pub fn image(category: &Category, item: &Value, manifest: &Manifest) -> Option<String> {
+    Some(manifest.image_path(&(category.image_name)(item)?))
+}
+
+pub fn id(item: &Value) -> Option<u64> {
+    item.get("id")?.as_u64()
+}
+
+/// Bugs and fish use "en" as language key, all other categories use "eng".
+pub fn english_name(item: &Value) -> Option<&str> {
+    let names = item.get("name")?;
+
+    names.get("eng")
+        .or_else(|| names.get("en"))?
+        .as_str()
+}
+
+/// Villager birthdays are dumped as `[day, month]`.
+pub fn birthday(item: &Value) -> Option<[u8; 2]> {
+    match item.get("birthday")?.as_array()?.as_slice() {
+        [day, month] => Some([day.as_u64()? as u8, month.as_u64()? as u8]),
+        _ => None,
+    }
+}
@@ -0,0 +1,253 @@
+use std::collections::BTreeMap;
+use select::document::Document;
+use select::predicate::*;
+use failure::Fallible;
+use rayon::prelude::*;
+use crate::common::*;
+use crate::rarity::Rarity;
+
+const QUOTES: &[char] = &['"', '“', '”', '„', '»', '«', ' '];
+
+/// A wiki with detail pages of critters, and how its texts are worded.
+#[derive(Debug)]
+pub struct Wiki {
+    /// Language of the texts, as in the names of critters
+    pub language: &'static str,
+    base_url: &'static str,
+    /// Keywords of the heading of the section with the museum description
+    museum_headings: &'static [&'static str],
+    /// How catch phrases start
+    catch_phrase_starts: &'static [&'static str],
+}
+
+/// The english wiki comes first, it has the infobox fields the other data is taken from.
+pub const WIKIS: &[Wiki] = &[
+    Wiki {
+        language: "en",
+        base_url: "https://animalcrossing.fandom.com/wiki/",
+        museum_headings: &["museum", "donation", "blathers"],
+        catch_phrase_starts: &["I caught", "I got"],
+    },
+    Wiki {
+        language: "de",
+        base_url: "https://animalcrossing.fandom.com/de/wiki/",
+        museum_headings: &["museum", "spende", "eugen"],
+        catch_phrase_starts: &["Ich habe", "Ich hab"],
+    },
+];
+
+/// Placeholder of names that are missing on the german wiki
+const MISSING_NAME: &str = "TBD";
+
+/// The parts of a critter's wiki detail page that are of interest.
+#[derive(Debug, Default)]
+pub struct Details {
+    /// Language of the wiki the page is from
+    pub language: &'static str,
+    /// Infobox fields, keyed by lowercase label
+    pub fields: BTreeMap<String, String>,
+    /// Text paragraphs of the article
+    pub paragraphs: Vec<String>,
+    /// Text paragraphs by lowercase section heading, in page order
+    pub sections: Vec<(String, Vec<String>)>,
+}
+
+impl Details {
+    pub fn field(&self, label: &str) -> Option<&str> {
+        self.fields.get(label).map(String::as_str)
+    }
+
+    /// Paragraphs of the first section whose heading contains one of the keywords.
+    pub fn section(&self, keywords: &[&str]) -> Option<&[String]> {
+        self.sections.iter()
+            .find(|(heading, _)| keywords.iter().any(|keyword| heading.contains(keyword)))
+            .map(|(_, paragraphs)| paragraphs.as_slice())
+    }
+
+    /// Blathers' description shown when donating to the museum
+    pub fn museum_description(&self) -> Option<String> {
+        let paragraphs = self.section(self.wiki()?.museum_headings)?;
+
+        if paragraphs.is_empty() {
+            return None;
+        }
+
+        Some(paragraphs.join("\n"))
+    }
+
+    /// The message shown when catching the critter, e.g. "I caught a tarantula! ..."
+    pub fn catch_phrase(&self) -> Option<String> {
+        let starts = self.wiki()?.catch_phrase_starts;
+        let from_field = self.fields.iter()
+            .find(|(label, _)| label.contains("catch"))
+            .map(|(_, value)| value.as_str());
+        let from_text = || self.paragraphs.iter()
+            .map(|paragraph| paragraph.trim_matches(QUOTES))
+            .find(|paragraph| starts.iter().any(|start| paragraph.starts_with(start)));
+
+        from_field
+            .or_else(from_text)
+            .map(|phrase| phrase.trim_matches(QUOTES).to_owned())
+    }
+
+    /// The rarity rating, with a warning if the page has one that is not known.
+    pub fn rarity(&self) -> Option<Rarity> {
+        let text = self.field("rarity")?;
+        let rarity = Rarity::parse(text);
+
+        if rarity.is_none() {
+            eprintln!("Unknown rarity '{}'", text);
+        }
+
+        rarity
+    }
+
+    /// The price of the first infobox field mentioning one of the buyers, e.g. "flick"
+    pub fn price_at(&self, buyers: &[&str]) -> Option<i32> {
+        self.fields.iter()
+            .find(|(label, _)| buyers.iter().any(|buyer| label.contains(buyer)))
+            .and_then(|(_, value)| parse_price(value))
+    }
+
+    /// Whether any paragraph contains one of the phrases as whole words, ignoring case.
+    pub fn mentions(&self, phrases: &[&str]) -> bool {
+        self.paragraphs.iter()
+            .map(|paragraph| words(paragraph))
+            .any(|words| phrases.iter().any(|phrase| find_keyword(&words, phrase).is_some()))
+    }
+
+    fn wiki(&self) -> Option<&'static Wiki> {
+        WIKIS.iter().find(|wiki| wiki.language == self.language)
+    }
+}
+
+/// Fetches the detail pages of critters with the given names from every wiki in parallel,
+/// keyed by lowercase english name, with the english page first.
+/// Pages that fail to download or parse are left out with a warning.
+pub fn fetch_all<'a>(names: impl IntoIterator<Item = &'a BTreeMap<String, String>>) -> BTreeMap<String, Vec<Details>> {
+    let pages = names.into_iter()
+        .filter_map(|names| Some((names.get("en")?.to_lowercase(), names)))
+        .flat_map(|(key, names)| WIKIS.iter()
+            .filter_map(move |wiki| names.get(wiki.language).map(|name| (wiki, name)))
+            .filter(|(_, name)| name.as_str() != MISSING_NAME)
+            .map(move |(wiki, name)| (key.clone(), wiki, name.clone()))
+        )
+        .collect::<Vec<_>>();
+
+    let pages = pages.par_iter()
+        .filter_map(|(key, wiki, name)| match fetch(wiki, name) {
+            Ok(details) => Some((key.clone(), details)),
+            Err(err) => {
+                eprintln!("Failed to fetch {} details of '{}': {}", wiki.language, name, err);
+                None
+            },
+        })
+        .collect::<Vec<_>>();
+
+    let mut details = BTreeMap::<_, Vec<_>>::new();
+
+    for (key, page) in pages {
+        details.entry(key).or_default().push(page);
+    }
+
+    details
+}
+
+pub fn fetch(wiki: &'static Wiki, name: &str) -> Fallible<Details> {
+    let url = format!("{}{}", wiki.base_url, page_name(name));
+    println!("Fetching '{}'", url);
+    let page = download_page(&url)?;
+
+    Ok(parse(wiki, &page))
+}
+
+fn parse(wiki: &'static Wiki, page: &Document) -> Details {
+    let fields = page.find(Class("pi-data"))
+        .filter_map(|field| {
+            let label = field.find(Class("pi-data-label")).next()?.text();
+            let value = field.find(Class("pi-data-value")).next()?.text();
+
+            Some((label.trim().to_lowercase(), value.trim().to_owned()))
+        })
+        .collect();
+
+    let mut paragraphs = Vec::new();
+    let mut sections = vec![(String::new(), Vec::new())];
+
+    let nodes = page.find(Attr("id", "mw-content-text").descendant(
+        Name("h2").or(Name("h3")).or(Name("p")).or(Name("dd"))
+    ));
+
+    for node in nodes {
+        let text = node.text().trim().to_owned();
+
+        match node.name() {
+            Some("h2") | Some("h3") => {
+                let heading = text.trim_end_matches("[edit]").trim().to_lowercase();
+                sections.push((heading, Vec::new()));
+            },
+            _ if text.is_empty() => {},
+            _ => {
+                if let Some((_, section)) = sections.last_mut() {
+                    section.push(text.clone());
+                }
+
+                paragraphs.push(text);
+            },
+        }
+    }
+
+    Details { language: wiki.language, fields, paragraphs, sections }
+}
+
+/// Wiki page names start with a capital letter and use underscores instead of spaces.
+fn page_name(name: &str) -> String {
+    let mut chars = name.trim().chars();
+
+    let name = match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+        None => String::new(),
+    };
+
+    name.replace(' ', "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(language: &'static str, sections: &[(&str, &[&str])]) -> Details {
+        let sections = sections.iter()
+            .map(|&(heading, paragraphs)| (heading.into(), paragraphs.iter().map(|&paragraph| paragraph.into()).collect()))
+            .collect::<Vec<(String, Vec<String>)>>();
+
+        Details {
+            language,
+            paragraphs: sections.iter().flat_map(|(_, paragraphs)| paragraphs.clone()).collect(),
+            sections,
+            ..Details::default()
+        }
+    }
+
+    #[test]
+    fn english_texts() {
+        let details = details("en", &[
+            ("", &["\"I caught a tarantula! Eek!\""]),
+            ("donating to the museum", &["Hoo! The tarantula is a frightful beast."]),
+        ]);
+
+        assert_eq!(details.catch_phrase().as_deref(), Some("I caught a tarantula! Eek!"));
+        assert_eq!(details.museum_description().as_deref(), Some("Hoo! The tarantula is a frightful beast."));
+    }
+
+    #[test]
+    fn german_texts() {
+        let details = details("de", &[
+            ("", &["„Ich habe eine Tarantel gefangen! Iiiiih!“", "I caught a tarantula!"]),
+            ("eugens beschreibung", &["Huhu! Die Tarantel ist ein furchterregendes Biest."]),
+        ]);
+
+        assert_eq!(details.catch_phrase().as_deref(), Some("Ich habe eine Tarantel gefangen! Iiiiih!"));
+        assert_eq!(details.museum_description().as_deref(), Some("Huhu! Die Tarantel ist ein furchterregendes Biest."));
+    }
+}
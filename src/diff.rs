@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::fs;
+use std::fmt::Write;
+use serde::*;
+use serde_json::{self as json, Value};
+use failure::Fallible;
+use itertools::Itertools;
+use crate::dataset::{self, CATEGORIES};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun",
+    "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+#[derive(Debug, Default, Serialize)]
+pub struct Changelog {
+    pub categories: BTreeMap<&'static str, CategoryChanges>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct CategoryChanges {
+    pub added: Vec<ItemRef>,
+    pub removed: Vec<ItemRef>,
+    pub changed: Vec<ItemChanges>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemRef {
+    pub id: u64,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ItemChanges {
+    #[serde(flatten)]
+    pub item: ItemRef,
+    pub changes: Vec<Change>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag="kind", rename_all="snake_case")]
+pub enum Change {
+    Price { old: Value, new: Value },
+    Months { hemisphere: &'static str, old: Vec<u8>, new: Vec<u8> },
+    Time { old: Value, new: Value },
+    Translation { field: String, language: String, old: Option<String>, new: String },
+    Sources { old: Value, new: Value },
+}
+
+/// Compares two data directories and writes `<out>.md` and `<out>.json`.
+pub fn diff(old_dir: impl AsRef<Path>, new_dir: impl AsRef<Path>, out: &str) -> Fallible<()> {
+    let changelog = compare(old_dir, new_dir)?;
+
+    let markdown = render_markdown(&changelog)?;
+    fs::write(format!("{}.md", out), &markdown)?;
+    fs::write(format!("{}.json", out), json::to_string_pretty(&changelog)?)?;
+
+    print!("{}", markdown);
+
+    Ok(())
+}
+
+pub fn compare(old_dir: impl AsRef<Path>, new_dir: impl AsRef<Path>) -> Fallible<Changelog> {
+    let mut changelog = Changelog::default();
+
+    for category in CATEGORIES {
+        let old_items = by_id(dataset::load(old_dir.as_ref(), category)?);
+        let new_items = by_id(dataset::load(new_dir.as_ref(), category)?);
+        let mut changes = CategoryChanges::default();
+
+        for (&id, new_item) in &new_items {
+            match old_items.get(&id) {
+                None => changes.added.push(item_ref(id, new_item)),
+                Some(old_item) => {
+                    let item_changes = compare_items(old_item, new_item);
+
+                    if !item_changes.is_empty() {
+                        changes.changed.push(ItemChanges {
+                            item: item_ref(id, new_item),
+                            changes: item_changes,
+                        });
+                    }
+                },
+            }
+        }
+
+        for (&id, old_item) in &old_items {
+            if !new_items.contains_key(&id) {
+                changes.removed.push(item_ref(id, old_item));
+            }
+        }
+
+        if !changes.added.is_empty() || !changes.removed.is_empty() || !changes.changed.is_empty() {
+            changelog.categories.insert(category.name, changes);
+        }
+    }
+
+    Ok(changelog)
+}
+
+fn by_id(items: Vec<Value>) -> BTreeMap<u64, Value> {
+    items.into_iter()
+        .filter_map(|item| Some((dataset::id(&item)?, item)))
+        .collect()
+}
+
+/// Dumps before the `prices` struct had a single `price` with -1 for unknown prices,
+/// which only becomes the sell price.
+fn prices(item: &Value) -> Value {
+    match (item.get("prices"), item.get("price").and_then(Value::as_i64)) {
+        (Some(prices), _) => prices.clone(),
+        (None, Some(price)) if price >= 0 => json::json!({ "sell": price }),
+        (None, Some(_)) => json::json!({ "sell": null }),
+        (None, None) => Value::Null,
+    }
+}
+
+/// Compares only the prices both dumps have, so prices added to the dump are no change.
+fn prices_changed(old: &Value, new: &Value) -> bool {
+    match (old.as_object(), new.as_object()) {
+        (Some(old), Some(new)) => old.iter()
+            .any(|(kind, old_price)| new.get(kind).map(|new_price| new_price != old_price).unwrap_or(false)),
+        _ => old != new,
+    }
+}
+
+fn item_ref(id: u64, item: &Value) -> ItemRef {
+    ItemRef {
+        id,
+        name: dataset::english_name(item).unwrap_or("???").into(),
+    }
+}
+
+fn compare_items(old: &Value, new: &Value) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let field = |item: &Value, name: &str| item.get(name).cloned().unwrap_or(Value::Null);
+
+    if prices_changed(&prices(old), &prices(new)) {
+        changes.push(Change::Price {
+            old: prices(old),
+            new: prices(new),
+        });
+    }
+
+    for &(hemisphere, key) in &[("north", "months_north"), ("south", "months_south")] {
+        let old = months(&field(old, key));
+        let new = months(&field(new, key));
+
+        if old != new {
+            changes.push(Change::Months { hemisphere, old, new });
+        }
+    }
+
+    if field(old, "time") != field(new, "time") {
+        changes.push(Change::Time {
+            old: field(old, "time"),
+            new: field(new, "time"),
+        });
+    }
+
+    if let Some(fields) = new.as_object() {
+        for (name, texts) in fields {
+            let texts = match texts.as_object() {
+                Some(texts) if texts.values().all(Value::is_string) => texts,
+                _ => continue,
+            };
+
+            for (language, text) in texts {
+                let text = text.as_str().unwrap_or_default();
+                let old_text = old.get(name)
+                    .and_then(|texts| texts.get(language))
+                    .and_then(Value::as_str);
+
+                if old_text != Some(text) {
+                    changes.push(Change::Translation {
+                        field: name.clone(),
+                        language: language.clone(),
+                        old: old_text.map(<_>::into),
+                        new: text.into(),
+                    });
+                }
+            }
+        }
+    }
+
+    if field(old, "sources") != field(new, "sources") {
+        changes.push(Change::Sources {
+            old: field(old, "sources"),
+            new: field(new, "sources"),
+        });
+    }
+
+    changes
+}
+
+/// Converts a list of 12 availability flags to 1-based month numbers.
+fn months(flags: &Value) -> Vec<u8> {
+    flags.as_array()
+        .into_iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, available)| available.as_bool() == Some(true))
+        .map(|(month, _)| month as u8 + 1)
+        .collect()
+}
+
+fn render_markdown(changelog: &Changelog) -> Fallible<String> {
+    let mut md = String::new();
+
+    writeln!(md, "# Changelog")?;
+
+    if changelog.categories.is_empty() {
+        writeln!(md, "\nNo changes.")?;
+    }
+
+    for (category, changes) in &changelog.categories {
+        let title = dataset::category(category)?.title;
+        writeln!(md, "\n## {}\n", title)?;
+
+        for item in &changes.added {
+            writeln!(md, "- Added **{}** (#{})", item.name, item.id)?;
+        }
+
+        for item in &changes.removed {
+            writeln!(md, "- Removed **{}** (#{})", item.name, item.id)?;
+        }
+
+        for item in &changes.changed {
+            writeln!(md, "- **{}** (#{})", item.item.name, item.item.id)?;
+
+            for change in &item.changes {
+                writeln!(md, "  - {}", describe(change))?;
+            }
+        }
+    }
+
+    Ok(md)
+}
+
+fn describe(change: &Change) -> String {
+    match change {
+        Change::Price { old, new } => format!(
+            "Price changed from {} to {}",
+            describe_prices(old), describe_prices(new),
+        ),
+        Change::Months { hemisphere, old, new } => format!(
+            "Months ({}) changed from {} to {}",
+            hemisphere, month_names(old), month_names(new),
+        ),
+        Change::Time { old, new } => format!("Time changed from {} to {}", old, new),
+        Change::Translation { field, language, old: None, new } => format!(
+            "New translation of {} ({}): {}", field, language, new,
+        ),
+        Change::Translation { field, language, old: Some(old), new } => format!(
+            "Translation of {} ({}) changed from \"{}\" to \"{}\"", field, language, old, new,
+        ),
+        Change::Sources { .. } => "Breeding sources changed".into(),
+    }
+}
+
+/// E.g. "100 Bells (150 Bells at the special buyer)" or "1245 Bells (4980 Bells to buy)"
+fn describe_prices(prices: &Value) -> String {
+    let bells = |kind| prices.get(kind)
+        .and_then(Value::as_i64)
+        .map(|price| format!("{} Bells", price));
+
+    let details = vec![
+        bells("special").map(|special| format!("{} at the special buyer", special)),
+        bells("buy").map(|buy| format!("{} to buy", buy)),
+    ];
+    let details = details.into_iter().flatten().join(", ");
+
+    match (bells("sell"), details.is_empty()) {
+        (Some(sell), true) => sell,
+        (Some(sell), false) => format!("{} ({})", sell, details),
+        (None, true) => "unknown".into(),
+        (None, false) => format!("unknown ({})", details),
+    }
+}
+
+fn month_names(months: &[u8]) -> String {
+    if months.is_empty() {
+        return "none".into();
+    }
+
+    months.iter()
+        .map(|&month| MONTHS[month as usize - 1])
+        .join(", ")
+}
@@ -0,0 +1,205 @@
+use std::path::Path;
+use std::fs;
+use serde::*;
+use serde_json as json;
+use image::{DynamicImage, Rgba, RgbaImage, imageops::{self, FilterType}};
+use failure::Fallible;
+use itertools::Itertools;
+use crate::manifest::Manifest;
+
+/// Images whose hashes differ in at most this many bits are considered near-identical.
+const MAX_DISTANCE: u32 = 4;
+
+/// A hash shared by at least this many different items is assumed to be a placeholder.
+const MIN_PLACEHOLDER_USES: usize = 3;
+
+/// Folder (inside the image folder) with known placeholder images, e.g. the wiki's "bildfehlt" image.
+const PLACEHOLDER_FOLDER: &str = "placeholders";
+
+pub const REPORT_FILE: &str = "duplicates.json";
+
+#[derive(Debug, Default, Serialize)]
+pub struct Report {
+    pub duplicates: Vec<Duplicate>,
+    pub placeholders: Vec<Placeholder>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Duplicate {
+    pub files: [ImageRef; 2],
+    pub distance: u32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Placeholder {
+    pub file: ImageRef,
+    pub reason: PlaceholderReason,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all="snake_case")]
+pub enum PlaceholderReason {
+    /// Matches an image from the placeholder folder
+    Known { placeholder: String },
+    /// Shares its hash with many other items
+    Shared { uses: usize },
+    /// Has (almost) no visible content
+    Blank,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImageRef {
+    pub name: String,
+    pub category: String,
+    pub id: usize,
+}
+
+struct HashedImage {
+    image: ImageRef,
+    hash: u64,
+    is_blank: bool,
+}
+
+/// Hashes all images of the manifest in `image_dir` and writes a report of
+/// near-identical images and probable placeholders to `duplicates.json`.
+pub fn detect(image_dir: impl AsRef<Path>) -> Fallible<Report> {
+    let image_dir = image_dir.as_ref();
+    let manifest = Manifest::load(image_dir)?;
+    let known_placeholders = hash_placeholders(&image_dir.join(PLACEHOLDER_FOLDER))?;
+    let mut images = Vec::new();
+
+    for (name, entry) in &manifest.entries {
+        let output = match entry.outputs.first() {
+            Some(output) => output,
+            None => continue,
+        };
+        let source = match image::open(image_dir.join(&output.path)) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("Skipping '{}': {}", output.path, err);
+                continue;
+            },
+        };
+        let (hash, is_blank) = difference_hash(&source);
+
+        images.push(HashedImage {
+            image: ImageRef {
+                name: name.clone(),
+                category: entry.category.clone(),
+                id: entry.id,
+            },
+            hash,
+            is_blank,
+        });
+    }
+
+    let mut report = Report::default();
+
+    for (a, b) in images.iter().tuple_combinations() {
+        let distance = (a.hash ^ b.hash).count_ones();
+        let is_same_item = a.image.category == b.image.category && a.image.id == b.image.id;
+        // Different variants of one item (e.g. hi-res images) are expected to look alike,
+        // except for the fakes of art pieces.
+        let is_fake_pair = a.image.name.ends_with("_fake") || b.image.name.ends_with("_fake");
+
+        if distance <= MAX_DISTANCE && (!is_same_item || is_fake_pair) {
+            report.duplicates.push(Duplicate {
+                files: [a.image.clone(), b.image.clone()],
+                distance,
+            });
+        }
+    }
+
+    let hash_uses = images.iter()
+        .map(|image| (image.hash, (&image.image.category, image.image.id)))
+        .into_group_map();
+
+    for image in &images {
+        let known = known_placeholders.iter()
+            .find(|(_, hash)| (image.hash ^ *hash).count_ones() <= MAX_DISTANCE);
+        let uses = hash_uses[&image.hash].iter().unique().count();
+
+        let reason = if let Some((placeholder, _)) = known {
+            PlaceholderReason::Known { placeholder: placeholder.clone() }
+        } else if image.is_blank {
+            PlaceholderReason::Blank
+        } else if uses >= MIN_PLACEHOLDER_USES {
+            PlaceholderReason::Shared { uses }
+        } else {
+            continue;
+        };
+
+        report.placeholders.push(Placeholder {
+            file: image.image.clone(),
+            reason,
+        });
+    }
+
+    for duplicate in &report.duplicates {
+        let [a, b] = &duplicate.files;
+        println!("Near-identical (distance {}): '{}' ({} #{}) and '{}' ({} #{})",
+            duplicate.distance, a.name, a.category, a.id, b.name, b.category, b.id);
+    }
+
+    for placeholder in &report.placeholders {
+        println!("Probable placeholder: '{}' ({:?})", placeholder.file.name, placeholder.reason);
+    }
+
+    fs::write(image_dir.join(REPORT_FILE), json::to_string_pretty(&report)?)?;
+
+    Ok(report)
+}
+
+fn hash_placeholders(dir: &Path) -> Fallible<Vec<(String, u64)>> {
+    let mut hashes = Vec::new();
+
+    if !dir.exists() {
+        return Ok(hashes);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let source = match image::open(&path) {
+            Ok(source) => source,
+            Err(_) => continue,
+        };
+        let (hash, _) = difference_hash(&source);
+
+        hashes.push((path.display().to_string(), hash));
+    }
+
+    Ok(hashes)
+}
+
+/// Computes a 64 bit difference hash ("dHash") and whether the image is blank.
+/// Transparent areas are treated as white.
+fn difference_hash(source: &DynamicImage) -> (u64, bool) {
+    let source = source.to_rgba8();
+    let mut flattened = RgbaImage::from_pixel(source.width(), source.height(), Rgba([255, 255, 255, 255]));
+    imageops::overlay(&mut flattened, &source, 0, 0);
+
+    let thumbnail = DynamicImage::ImageRgba8(flattened)
+        .resize_exact(9, 8, FilterType::Triangle)
+        .to_luma8();
+
+    let mut hash = 0;
+
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = thumbnail.get_pixel(x, y)[0];
+            let right = thumbnail.get_pixel(x + 1, y)[0];
+
+            hash <<= 1;
+            hash |= (left < right) as u64;
+        }
+    }
+
+    let (min, max) = thumbnail.pixels()
+        .map(|pixel| pixel[0])
+        .minmax()
+        .into_option()
+        .unwrap_or((0, 0));
+    let is_blank = max - min <= 4;
+
+    (hash, is_blank)
+}
@@ -5,15 +5,27 @@ use serde::*;
 use failure::{Fallible, format_err};
 use itertools::Itertools;
 use crate::common::*;
+use crate::pipeline::{Pipeline, Trim};
 use crate::id;
+use crate::prices::Prices;
+use crate::location::Location;
+use crate::spawn::{self, SpawnCondition};
+use crate::details;
+use crate::rarity::Rarity;
 
 #[derive(Debug, Serialize)]
 pub struct Fish {
     pub id: usize,
     #[serde(rename="name")]
     pub names: BTreeMap<String, String>,
-    pub price: i32,
-    pub location: String,
+    pub prices: Prices,
+    pub location: Vec<Location>,
+    pub spawn_conditions: Vec<SpawnCondition>,
+    #[serde(rename="museum_description")]
+    pub museum_descriptions: BTreeMap<String, String>,
+    #[serde(rename="catch_phrase")]
+    pub catch_phrases: BTreeMap<String, String>,
+    pub rarity: Option<Rarity>,
     pub shadow: Shadow,
     pub time: Vec<[u8; 2]>,
     #[serde(rename="months_north")]
@@ -26,13 +38,38 @@ pub struct Fish {
     pub hi_res_image_url: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Shadow {
-    size: i8,
-    is_narrow: bool,
-    has_fin: bool,
+    pub size: ShadowSize,
+    pub has_fin: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ShadowSize {
+    Tiny,
+    Small,
+    Medium,
+    Large,
+    XLarge,
+    Huge,
+    Narrow,
+    Long,
 }
 
+/// (size, size class as shown in the wiki, spellings)
+///
+/// Spellings are lowercase, with hyphens replaced by spaces.
+const SHADOW_SIZES: &[(ShadowSize, Option<u8>, &[&str])] = &[
+    (ShadowSize::Tiny, Some(1), &["tiny", "smallest", "very small", "x small"]),
+    (ShadowSize::Small, Some(2), &["small"]),
+    (ShadowSize::Medium, Some(3), &["medium"]),
+    (ShadowSize::Large, Some(4), &["large"]),
+    (ShadowSize::XLarge, Some(5), &["x large", "very large", "extra large", "xlarge", "xl"]),
+    (ShadowSize::Huge, Some(6), &["huge", "largest", "xx large", "xxl"]),
+    (ShadowSize::Narrow, None, &["narrow", "thin"]),
+    (ShadowSize::Long, None, &["long"]),
+];
+
 pub fn fetch_all() -> Fallible<Vec<Fish>> {
     let page = download_page("https://animalcrossing.fandom.com/wiki/Fish_(New_Horizons)")?;
     let mut fish = parse_fish(page)?;
@@ -47,6 +84,31 @@ pub fn fetch_all() -> Fallible<Vec<Fish>> {
         }
     }
 
+    let details = details::fetch_all(fish.iter().map(|fish| &fish.names));
+
+    for fish in &mut fish {
+        let pages = details.get(&fish.names["en"].to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let details = pages.iter().find(|page| page.language == "en");
+        fish.spawn_conditions = spawn::conditions(&fish.location, details);
+
+        for page in pages {
+            if let Some(description) = page.museum_description() {
+                fish.museum_descriptions.insert(page.language.into(), description);
+            }
+
+            if let Some(phrase) = page.catch_phrase() {
+                fish.catch_phrases.insert(page.language.into(), phrase);
+            }
+        }
+
+        fish.rarity = details.and_then(|details| details.rarity());
+
+        let special_price = details.and_then(|details| details.price_at(&["c.j.", "cj"]));
+        fish.prices = fish.prices.with_special_buyer(special_price);
+    }
+
     Ok(fish)
 }
 
@@ -87,20 +149,15 @@ fn parse_fish(page: Document) -> Fallible<Vec<Fish>> {
             );
 
         let price = north_cols.get(2)
-            .and_then(|price| parse_price(price.text()))
-            .unwrap_or(-1);
+            .and_then(|price| parse_price(price.text()));
         
         let location = north_cols.get(3)
-            .and_then(|location| parse_text(location.text()))
-            .unwrap_or_else(|| "???".into());
+            .map(|location| Location::parse_list(location.text()))
+            .unwrap_or_else(Vec::new);
         
         let shadow = north_cols.get(4)
-            .map(|shadow| parse_shadow(shadow.text()))
-            .unwrap_or(Shadow {
-                size: -1,
-                is_narrow: false,
-                has_fin: false,
-            });
+            .ok_or_else(|| format_err!("Missing shadow of '{}'", names["en"]))
+            .and_then(|shadow| Shadow::parse(shadow.text()))?;
 
         let time = north_cols.get(5)
             .and_then(|time| parse_time_slots(time.text()))
@@ -128,8 +185,12 @@ fn parse_fish(page: Document) -> Fallible<Vec<Fish>> {
             id: id::fish(&names["en"]),
             image_url,
             names,
-            price,
+            prices: Prices::sell(price),
             location,
+            spawn_conditions: Vec::new(),
+            museum_descriptions: BTreeMap::new(),
+            catch_phrases: BTreeMap::new(),
+            rarity: None,
             shadow,
             north_months,
             south_months,
@@ -143,18 +204,81 @@ fn parse_fish(page: Document) -> Fallible<Vec<Fish>> {
     Ok(fishs)
 }
 
-fn parse_shadow(shadow: impl AsRef<str>) -> Shadow {
-    let shadow = shadow.as_ref().to_lowercase();
-    let is_narrow = shadow.contains("narrow");
-    let has_fin = shadow.contains("fin");
-    let size = shadow
-        .chars()
-        .filter(char::is_ascii_digit)
-        .collect::<String>()
-        .parse::<i8>()
-        .unwrap_or(-1);
-
-    Shadow { size, is_narrow, has_fin }
+impl Shadow {
+    /// Parses shadows like "Medium (3)", "X Large (With Fin) (5)" or "Narrow".
+    /// Fails on any text that is not a known shadow.
+    pub fn parse(text: impl AsRef<str>) -> Fallible<Self> {
+        let text = text.as_ref().trim().to_lowercase();
+        let normalized = text.replace('-', " ");
+        let mut has_fin = false;
+        let mut class = None;
+        let mut words = Vec::new();
+
+        for token in normalized.split(|c: char| c.is_whitespace() || "()/,".contains(c)) {
+            match token {
+                "" | "with" | "w" => {},
+                "fin" | "finned" => has_fin = true,
+                token => match token.parse::<u8>() {
+                    Ok(number) => class = Some(number),
+                    Err(_) => words.push(token),
+                },
+            }
+        }
+
+        let label = words.join(" ");
+        let by_label = ShadowSize::from_label(&label);
+        let by_class = class.and_then(ShadowSize::from_class);
+
+        let size = match (by_label, by_class) {
+            (Some(size), _) if size.class().is_none() => size,
+            (Some(size), Some(class_size)) if size != class_size => {
+                eprintln!("Shadow '{}' has a mismatching size class, using {:?}", text, class_size);
+                class_size
+            },
+            (Some(size), _) => size,
+            (None, Some(size)) if label.is_empty() => size,
+            _ => return Err(format_err!("Unknown shadow '{}'", text)),
+        };
+
+        Ok(Shadow { size, has_fin })
+    }
+}
+
+impl ShadowSize {
+    fn from_label(label: &str) -> Option<Self> {
+        SHADOW_SIZES.iter()
+            .find(|(_, _, spellings)| spellings.contains(&label))
+            .map(|&(size, ..)| size)
+    }
+
+    fn from_class(class: u8) -> Option<Self> {
+        SHADOW_SIZES.iter()
+            .find(|&&(_, other, _)| other == Some(class))
+            .map(|&(size, ..)| size)
+    }
+
+    /// Size class from 1 (tiny) to 6 (huge), narrow and long shadows have none.
+    pub fn class(self) -> Option<u8> {
+        SHADOW_SIZES.iter()
+            .find(|&&(size, ..)| size == self)
+            .and_then(|&(_, class, _)| class)
+    }
+}
+
+/// Keeps the `{size, is_narrow, has_fin}` shape of earlier dumps, with -1 as size of narrow and long shadows.
+/// Long shadows are only told apart from narrow ones by `is_narrow` being false.
+impl Serialize for Shadow {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let size = self.size.class().map(|class| class as i8).unwrap_or(-1);
+
+        let mut shadow = serializer.serialize_struct("Shadow", 3)?;
+        shadow.serialize_field("size", &size)?;
+        shadow.serialize_field("is_narrow", &(self.size == ShadowSize::Narrow))?;
+        shadow.serialize_field("has_fin", &self.has_fin)?;
+        shadow.end()
+    }
 }
 
 impl HasFiles for Fish {
@@ -163,17 +287,21 @@ impl HasFiles for Fish {
 
         if let Some(image_url) = &self.image_url {
             files.push(File {
-                name: format!("f{}.png", self.id),
+                name: format!("f{}", self.id),
+                id: self.id,
+                category: "fish",
                 url: image_url.clone(),
-                transform: convert_image_to_png,
+                pipeline: Pipeline::default().with_trim(Trim::default()),
             })
         }
 
         if let Some(hi_res_image_url) = &self.hi_res_image_url {
             files.push(File {
-                name: format!("f{}_hi.png", self.id),
+                name: format!("f{}_hi", self.id),
+                id: self.id,
+                category: "fish",
                 url: hi_res_image_url.clone(),
-                transform: convert_image_to_png,
+                pipeline: Pipeline::default().with_trim(Trim::default()),
             })
         }
 
@@ -246,3 +374,62 @@ fn fetch_extra_info() -> Fallible<BTreeMap<String, ExtraInfo>> {
 
     Ok(extra_infos)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Shadow {
+        Shadow::parse(text).unwrap()
+    }
+
+    #[test]
+    fn sizes_with_class() {
+        assert_eq!(parse("Tiny (1)"), Shadow { size: ShadowSize::Tiny, has_fin: false });
+        assert_eq!(parse("Medium (3)"), Shadow { size: ShadowSize::Medium, has_fin: false });
+        assert_eq!(parse("Huge (6)"), Shadow { size: ShadowSize::Huge, has_fin: false });
+    }
+
+    #[test]
+    fn spellings_of_x_large() {
+        let x_large = Shadow { size: ShadowSize::XLarge, has_fin: false };
+
+        assert_eq!(parse("X Large (5)"), x_large);
+        assert_eq!(parse("x-large"), x_large);
+        assert_eq!(parse("X-Large  (5)"), x_large);
+        assert_eq!(parse("Extra Large"), x_large);
+    }
+
+    #[test]
+    fn fins() {
+        assert_eq!(parse("Large (With Fin) (4)"), Shadow { size: ShadowSize::Large, has_fin: true });
+        assert_eq!(parse("Huge w/ fin"), Shadow { size: ShadowSize::Huge, has_fin: true });
+    }
+
+    #[test]
+    fn narrow_and_long() {
+        assert_eq!(parse("Narrow"), Shadow { size: ShadowSize::Narrow, has_fin: false });
+        assert_eq!(parse("Long"), Shadow { size: ShadowSize::Long, has_fin: false });
+    }
+
+    #[test]
+    fn class_wins_over_mismatching_label() {
+        assert_eq!(parse("Small (3)").size, ShadowSize::Medium);
+        assert_eq!(parse("(2)").size, ShadowSize::Small);
+    }
+
+    #[test]
+    fn serialized_shape() {
+        let json = |text| serde_json::to_value(parse(text)).unwrap();
+
+        assert_eq!(json("Large (With Fin) (4)"), serde_json::json!({ "size": 4, "is_narrow": false, "has_fin": true }));
+        assert_eq!(json("Narrow"), serde_json::json!({ "size": -1, "is_narrow": true, "has_fin": false }));
+        assert_eq!(json("Long"), serde_json::json!({ "size": -1, "is_narrow": false, "has_fin": false }));
+    }
+
+    #[test]
+    fn unknown_shadows() {
+        assert!(Shadow::parse("Gigantic").is_err());
+        assert!(Shadow::parse("").is_err());
+    }
+}
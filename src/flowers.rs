@@ -5,6 +5,7 @@ use serde::*;
 use failure::{Fallible};
 use itertools::Itertools;
 use crate::common::*;
+use crate::pipeline::Pipeline;
 use crate::id;
 
 #[derive(Debug, Serialize)]
@@ -238,17 +239,21 @@ impl HasFiles for Flower {
 
         if let Some(image_url) = &self.image_url {
             files.push(File {
-                name: format!("fl{}.png", self.id),
+                name: format!("fl{}", self.id),
+                id: self.id,
+                category: "flowers",
                 url: image_url.clone(),
-                transform: convert_image_to_png,
+                pipeline: Pipeline::default(),
             })
         }
 
         if let Some(hi_res_image_url) = &self.hi_res_image_url {
             files.push(File {
-                name: format!("fl{}_hi.png", self.id),
+                name: format!("fl{}_hi", self.id),
+                id: self.id,
+                category: "flowers",
                 url: hi_res_image_url.clone(),
-                transform: convert_image_to_png,
+                pipeline: Pipeline::default(),
             })
         }
 
@@ -5,20 +5,37 @@ use failure::{Fallible};
 use itertools::Itertools;
 use failure::format_err;
 use crate::common::*;
+use crate::pipeline::{Pipeline, Trim};
 use crate::id;
+use crate::prices::Prices;
 
 #[derive(Debug, Serialize)]
 pub struct Fossil {
     pub id: usize,
     #[serde(rename="name")]
     pub names: BTreeMap<String, String>,
-    pub price: i32,
+    pub prices: Prices,
+    /// The set this fossil is a part of, `None` for standalone fossils
+    pub set_id: Option<usize>,
     #[serde(skip)]
     pub image_url: Option<String>,
     #[serde(skip)]
     pub hi_res_image_url: Option<String>,
 }
 
+/// A multi-part fossil, e.g. the T. Rex consisting of skull, torso and tail.
+#[derive(Debug, Serialize)]
+pub struct FossilSet {
+    pub id: usize,
+    #[serde(rename="name")]
+    pub names: BTreeMap<String, String>,
+    /// IDs of the fossils belonging to this set
+    pub parts: Vec<usize>,
+    /// Sum of the prices of all parts, unknown if any part's price is unknown
+    pub prices: Prices,
+}
+
+
 pub fn fetch_all() -> Fallible<Vec<Fossil>> {
     let page = download_page("https://animalcrossing.fandom.com/wiki/Fossils_(New_Horizons)")?;
 
@@ -29,13 +46,21 @@ pub fn fetch_all() -> Fallible<Vec<Fossil>> {
         parse_table(table, 3)?
     };
 
-    let multipart_fossils = {
+    let mut multipart_fossils = {
         let table = page.find(Name("table"))
             .nth(5)
             .unwrap();
         parse_table(table, 4)?
     };
 
+    for fossil in &mut multipart_fossils {
+        let name = fossil.names["eng"].to_lowercase();
+        let set_name = find_set_name(&name)
+            .ok_or_else(|| format_err!("Could not find set of fossil '{}'", name))?;
+
+        fossil.set_id = Some(id::fossil_set(set_name));
+    }
+
     let mut fossils = standalone_fossils.into_iter()
         .chain(multipart_fossils)
         .enumerate()
@@ -93,14 +118,14 @@ fn parse_table(table: Node, skip_rows: usize) -> Fallible<Vec<Fossil>> {
             );
 
         let price = cols.get(2)
-            .and_then(|price| parse_price(price.text()))
-            .unwrap_or(-1);
+            .and_then(|price| parse_price(price.text()));
 
         let fossil = Fossil {
             id: id::fossil(&names["eng"]),
             image_url,
             names,
-            price,
+            prices: Prices::sell(price),
+            set_id: None,
             hi_res_image_url: None,
         };
 
@@ -110,15 +135,74 @@ fn parse_table(table: Node, skip_rows: usize) -> Fallible<Vec<Fossil>> {
     Ok(fossils)
 }
 
+/// Returns the set name of a multi-part fossil, as registered in `id::FOSSIL_SETS`.
+fn find_set_name(part_name: &str) -> Option<&'static str> {
+    let part_name = format!(" {} ", part_name);
+
+    id::FOSSIL_SETS.iter()
+        .find(|(_, key)| part_name.contains(&format!(" {} ", key)))
+        .map(|&(set_name, _)| set_name)
+}
+
+/// Capitalizes every word of a registered set name, e.g. "T. Rex".
+fn english_set_name(set_name: &str) -> String {
+    set_name.split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .join(" ")
+}
+
+/// Groups the multi-part fossils into their sets.
+pub fn sets(fossils: &[Fossil]) -> Vec<FossilSet> {
+    let mut sets = id::FOSSIL_SETS.iter()
+        .enumerate()
+        .map(|(id, &(set_name, _))| FossilSet {
+            id,
+            names: btreemap!{
+                "eng".into() => english_set_name(set_name),
+            },
+            parts: Vec::new(),
+            prices: Prices::sell(Some(0)),
+        })
+        .collect_vec();
+
+    for fossil in fossils {
+        let set = match fossil.set_id.and_then(|set_id| sets.iter_mut().find(|set| set.id == set_id)) {
+            Some(set) => set,
+            None => continue,
+        };
+
+        set.parts.push(fossil.id);
+
+        set.prices.sell = match (set.prices.sell, fossil.prices.sell) {
+            (Some(total), Some(price)) => Some(total + price),
+            _ => None,
+        };
+    }
+
+    sets.retain(|set| !set.parts.is_empty());
+    sets.sort_by_key(|set| set.id);
+
+    sets
+}
+
 impl HasFiles for Fossil {
     fn files(&self) -> Vec<File> {
         self
             .image_url
             .as_ref()
             .map(|image_url| vec![File {
-                name: format!("fo{}.png", self.id),
+                name: format!("fo{}", self.id),
+                id: self.id,
+                category: "fossils",
                 url: image_url.clone(),
-                transform: convert_image_to_png,
+                pipeline: Pipeline::default().with_trim(Trim::default()),
             }])
             .unwrap_or_default()
     }
@@ -201,3 +285,23 @@ fn fetch_extra_info() -> Fallible<BTreeMap<String, ExtraInfo>> {
 
     Ok(extra_infos)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn set_of_part() {
+        assert_eq!(find_set_name("megacero skull"), Some("megaloceros"));
+        assert_eq!(find_set_name("megalo torso"), Some("megalosaurus"));
+        assert_eq!(find_set_name("t. rex tail"), Some("t. rex"));
+        assert_eq!(find_set_name("amber"), None);
+    }
+
+    #[test]
+    fn english_set_names() {
+        assert_eq!(english_set_name("ankylosaurus"), "Ankylosaurus");
+        assert_eq!(english_set_name("sabertooth tiger"), "Sabertooth Tiger");
+        assert_eq!(english_set_name("t. rex"), "T. Rex");
+    }
+}
@@ -0,0 +1,84 @@
+use serde::*;
+
+/// A game of the Animal Crossing series.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Game {
+    AnimalForest,
+    AnimalForestPlus,
+    AnimalCrossing,
+    AnimalForestEPlus,
+    WildWorld,
+    CityFolk,
+    NewLeaf,
+    HappyHomeDesigner,
+    AmiiboFestival,
+    PocketCamp,
+    NewHorizons,
+    /// A game not known yet, with the text it was parsed from
+    Other(String),
+}
+
+/// (game, code, spellings)
+const GAMES: &[(Game, &str, &[&str])] = &[
+    (Game::AnimalForest, "af", &["af", "dnm", "animal forest", "dōbutsu no mori", "doubutsu no mori"]),
+    (Game::AnimalForestPlus, "af+", &["af+", "dnm+", "animal forest+", "animal forest plus"]),
+    (Game::AnimalCrossing, "ac", &["ac", "gc", "animal crossing"]),
+    (Game::AnimalForestEPlus, "afe+", &["afe+", "e+", "dnme+", "animal forest e+"]),
+    (Game::WildWorld, "ww", &["ww", "wild world", "animal crossing: wild world"]),
+    (Game::CityFolk, "cf", &["cf", "lgttc", "city folk", "let's go to the city", "animal crossing: let's go to the city"]),
+    (Game::NewLeaf, "nl", &["nl", "new leaf", "animal crossing: new leaf", "nl welcome amiibo", "welcome amiibo"]),
+    (Game::HappyHomeDesigner, "hhd", &["hhd", "happy home designer", "animal crossing: happy home designer"]),
+    (Game::AmiiboFestival, "amiibo_festival", &["amiibo festival", "animal crossing: amiibo festival"]),
+    (Game::PocketCamp, "pc", &["pc", "pocket camp", "animal crossing: pocket camp"]),
+    (Game::NewHorizons, "nh", &["nh", "new horizons", "animal crossing: new horizons"]),
+];
+
+impl Game {
+    pub fn parse(text: &str) -> Self {
+        let text = text.trim();
+        let lowercase = text.to_lowercase();
+
+        GAMES.iter()
+            .find(|(_, _, spellings)| spellings.contains(&&*lowercase))
+            .map(|(game, ..)| game.clone())
+            .unwrap_or_else(|| Game::Other(text.into()))
+    }
+
+    /// Parses a list of games like "AC, WW, CF, NL, NH".
+    pub fn parse_list(text: &str) -> Vec<Self> {
+        text
+            .split(&[',', ';', '/', '\n'][..])
+            .map(str::trim)
+            .filter(|game| !game.is_empty())
+            .flat_map(|game| match Game::parse(game) {
+                // Fall back to space separated abbreviations, e.g. "AC WW CF"
+                Game::Other(_) if game.contains(' ') => {
+                    let games = game.split_whitespace().map(Game::parse).collect::<Vec<_>>();
+
+                    if games.iter().all(|game| !matches!(game, Game::Other(_))) {
+                        games
+                    } else {
+                        vec![Game::Other(game.into())]
+                    }
+                },
+                game => vec![game],
+            })
+            .collect()
+    }
+
+    pub fn code(&self) -> &str {
+        match self {
+            Game::Other(text) => text,
+            game => GAMES.iter()
+                .find(|(other, ..)| other == game)
+                .map(|&(_, code, _)| code)
+                .expect("game missing from table"),
+        }
+    }
+}
+
+impl Serialize for Game {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.code())
+    }
+}
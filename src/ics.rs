@@ -0,0 +1,266 @@
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+use failure::Fallible;
+
+const PRODUCT_ID: &str = "-//ac_nh_wiki_dump//EN";
+
+/// Lines longer than this many bytes get folded, as required by RFC 5545
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Year the recurring events start in
+pub const BASE_YEAR: u16 = 2020;
+
+/// An iCalendar (RFC 5545) calendar.
+pub struct Calendar {
+    pub name: String,
+    pub events: Vec<Event>,
+}
+
+pub struct Event {
+    /// Unique and stable across exports, so calendar apps can update subscribed events
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub date: Date,
+    /// Start and end hour of a timed event, all-day events have none.
+    /// An end at or before the start lies on the next day.
+    pub hours: Option<[u8; 2]>,
+    pub recurrence: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Calendar {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            events: Vec::new(),
+        }
+    }
+
+    pub fn render(&self) -> Fallible<String> {
+        let mut lines = Vec::new();
+        let stamp = timestamp(SystemTime::now());
+
+        lines.push("BEGIN:VCALENDAR".to_owned());
+        lines.push("VERSION:2.0".to_owned());
+        lines.push(format!("PRODID:{}", PRODUCT_ID));
+        lines.push("CALSCALE:GREGORIAN".to_owned());
+        lines.push(format!("X-WR-CALNAME:{}", escape(&self.name)));
+
+        for event in &self.events {
+            lines.push("BEGIN:VEVENT".to_owned());
+            lines.push(format!("UID:{}", event.uid));
+            lines.push(format!("DTSTAMP:{}", stamp));
+            match event.hours {
+                Some([start, end]) => {
+                    let end_date = if end <= start || end >= 24 { event.date.next_day() } else { event.date };
+
+                    lines.push(format!("DTSTART:{}T{:02}0000", event.date, start));
+                    lines.push(format!("DTEND:{}T{:02}0000", end_date, end % 24));
+                },
+                None => {
+                    lines.push(format!("DTSTART;VALUE=DATE:{}", event.date));
+                    lines.push(format!("DTEND;VALUE=DATE:{}", event.date.next_day()));
+                },
+            }
+            lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+
+            if let Some(description) = &event.description {
+                lines.push(format!("DESCRIPTION:{}", escape(description)));
+            }
+
+            if let Some(recurrence) = &event.recurrence {
+                lines.push(format!("RRULE:{}", recurrence));
+            }
+
+            lines.push("TRANSP:TRANSPARENT".to_owned());
+            lines.push("END:VEVENT".to_owned());
+        }
+
+        lines.push("END:VCALENDAR".to_owned());
+
+        let mut ics = String::new();
+
+        for line in lines {
+            write!(ics, "{}\r\n", fold(&line))?;
+        }
+
+        Ok(ics)
+    }
+}
+
+impl Event {
+    /// An all-day event repeating every year.
+    pub fn yearly(uid: impl Into<String>, summary: impl Into<String>, [day, month]: [u8; 2]) -> Self {
+        Self {
+            uid: uid.into(),
+            summary: summary.into(),
+            description: None,
+            date: Date { year: BASE_YEAR, month, day },
+            hours: None,
+            recurrence: Some("FREQ=YEARLY".into()),
+        }
+    }
+
+    /// A timed event repeating every day of the given months, starting in `start_month`.
+    pub fn daily(uid: impl Into<String>, summary: impl Into<String>, hours: [u8; 2], start_month: u8, months: &[u8]) -> Self {
+        let months = months.iter().map(u8::to_string).collect::<Vec<_>>().join(",");
+
+        Self {
+            uid: uid.into(),
+            summary: summary.into(),
+            description: None,
+            date: Date { year: BASE_YEAR, month: start_month, day: 1 },
+            hours: Some(hours),
+            recurrence: Some(format!("FREQ=DAILY;BYMONTH={}", months)),
+        }
+    }
+
+    pub fn with_description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+}
+
+impl Date {
+    pub fn days_in_month(year: u16, month: u8) -> u8 {
+        match month {
+            2 if is_leap_year(year) => 29,
+            2 => 28,
+            4 | 6 | 9 | 11 => 30,
+            _ => 31,
+        }
+    }
+
+    pub fn next_day(self) -> Self {
+        let Date { year, month, day } = self;
+
+        if day < Self::days_in_month(year, month) {
+            Date { year, month, day: day + 1 }
+        } else if month < 12 {
+            Date { year, month: month + 1, day: 1 }
+        } else {
+            Date { year: year + 1, month: 1, day: 1 }
+        }
+    }
+}
+
+impl std::fmt::Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{:04}{:02}{:02}", self.year, self.month, self.day)
+    }
+}
+
+fn is_leap_year(year: u16) -> bool {
+    match (year % 4, year % 100, year % 400) {
+        (_, _, 0) => true,
+        (_, 0, _) => false,
+        (0, _, _) => true,
+        _ => false,
+    }
+}
+
+/// Formats a point in time as UTC date-time, e.g. `20200320T120000Z`.
+fn timestamp(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map(|duration| duration.as_secs()).unwrap_or(0);
+    let days = (seconds / 86400) as i64;
+    let seconds = seconds % 86400;
+
+    // Converts days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+        year, month, day,
+        seconds / 3600, seconds / 60 % 60, seconds % 60,
+    )
+}
+
+/// Escapes text values according to RFC 5545.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {},
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Splits long lines into continuation lines, without breaking up characters.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > MAX_LINE_LENGTH {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the line length
+            length = 1;
+        }
+
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_special_characters() {
+        assert_eq!(escape("Tom Nook; Isabelle, Blathers\\\r\nK.K."), r"Tom Nook\; Isabelle\, Blathers\\\nK.K.");
+    }
+
+    #[test]
+    fn keeps_short_lines() {
+        let line = "S".repeat(MAX_LINE_LENGTH);
+
+        assert_eq!(fold(&line), line);
+    }
+
+    #[test]
+    fn folds_long_lines() {
+        let line = format!("SUMMARY:{}", "x".repeat(150));
+        let folded = fold(&line);
+        let lines = folded.split("\r\n").collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert!(lines[1..].iter().all(|line| line.starts_with(' ')));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+
+    #[test]
+    fn folds_without_splitting_characters() {
+        let line = format!("SUMMARY:{}", "ä".repeat(60));
+        let folded = fold(&line);
+
+        assert!(folded.split("\r\n").all(|line| line.len() <= MAX_LINE_LENGTH));
+        assert_eq!(folded.replace("\r\n ", ""), line);
+    }
+}
@@ -26,6 +26,15 @@ pub fn fossil(name: impl AsRef<str>) -> usize {
     }
 }
 
+pub fn fossil_set(name: impl AsRef<str>) -> usize {
+    let name = name.as_ref().to_lowercase();
+
+    match FOSSIL_SETS.iter().position(|&(other, _)| other == name) {
+        Some(index) => index,
+        _ => panic!("fossil set '{}' has no id yet", name),
+    }
+}
+
 pub fn flower(name: impl AsRef<str>) -> usize {
     let name = name.as_ref().to_lowercase();
 
@@ -53,6 +62,29 @@ pub fn villager(name: impl AsRef<str>) -> usize {
     }
 }
 
+/// All known names of a category, in ID order.
+pub fn registry(category: &str) -> Option<Vec<&'static str>> {
+    let names = match category {
+        "bugs" => BUGS,
+        "fish" => FISH,
+        "fossils" => FOSSILS,
+        "fossil_sets" => return Some(FOSSIL_SETS.iter().map(|&(name, _)| name).collect()),
+        "flowers" => FLOWERS,
+        "art" => ART,
+        "villagers" => VILLAGERS,
+        _ => return None,
+    };
+
+    Some(names.to_vec())
+}
+
+/// Like the per-category functions, but returns `None` instead of panicking on unknown names.
+pub fn lookup(category: &str, name: impl AsRef<str>) -> Option<usize> {
+    let name = name.as_ref().to_lowercase();
+
+    registry(category)?.iter().position(|&other| other == name)
+}
+
 const BUGS: &[&str] = &[
     "common butterfly",
     "yellow butterfly",
@@ -295,6 +327,30 @@ const FOSSILS: &[&str] = &[
     "t. rex tail",
 ];
 
+/// (set name, name used in the part names of the set)
+pub const FOSSIL_SETS: &[(&str, &str)] = &[
+    ("ankylosaurus", "ankylo"),
+    ("archelon", "archelon"),
+    ("brachiosaurus", "brachio"),
+    ("deinonychus", "deinony"),
+    ("dimetrodon", "dimetrodon"),
+    ("diplodocus", "diplo"),
+    ("iguanodon", "iguanodon"),
+    ("mammoth", "mammoth"),
+    ("megaloceros", "megacero"),
+    ("megalosaurus", "megalo"),
+    ("ophthalmosaurus", "ophthalmo"),
+    ("pachycephalosaurus", "pachysaurus"),
+    ("parasaurolophus", "parasaur"),
+    ("plesiosaurus", "plesio"),
+    ("pteranodon", "ptera"),
+    ("quetzalcoatlus", "quetzal"),
+    ("sabertooth tiger", "sabertooth"),
+    ("spinosaurus", "spino"),
+    ("stegosaurus", "stego"),
+    ("triceratops", "tricera"),
+    ("t. rex", "t. rex"),
+];
 
 const FLOWERS: &[&str] = &[
     "red cosmos",
@@ -0,0 +1,64 @@
+//! Shared plumbing of enums backed by a table of localized labels,
+//! like personalities, species and critter locations.
+
+/// (value, code, english label, german label, further spellings or keywords)
+pub type Row<T> = (T, &'static str, &'static str, &'static str, &'static [&'static str]);
+
+/// Implements `all`, `code`, `label` and serialization as code for an enum
+/// whose variants each have a `Row` in `$table`.
+macro_rules! impl_labels {
+    ($type:ident, $table:ident) => {
+        impl $type {
+            pub fn all() -> impl Iterator<Item = $type> {
+                $table.iter().map(|&(value, ..)| value)
+            }
+
+            /// Stable code used in the dumped data
+            pub fn code(self) -> &'static str {
+                self.row().1
+            }
+
+            /// Label in the given language ("eng"/"en" or "deu"/"de")
+            pub fn label(self, language: &str) -> Option<&'static str> {
+                crate::labels::label(self.row(), language)
+            }
+
+            fn row(self) -> &'static crate::labels::Row<$type> {
+                $table.iter()
+                    .find(|&&(value, ..)| value == self)
+                    .expect(concat!(stringify!($type), " missing from table"))
+            }
+        }
+
+        impl serde::Serialize for $type {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.code())
+            }
+        }
+    };
+}
+
+pub fn label<T>(&(_, _, english, german, _): &Row<T>, language: &str) -> Option<&'static str> {
+    match language {
+        "eng" | "en" => Some(english),
+        "deu" | "de" => Some(german),
+        _ => None,
+    }
+}
+
+/// Finds the value whose code, label or spelling equals `text`,
+/// ignoring case and surrounding punctuation.
+pub fn parse<T: Copy>(table: &[Row<T>], text: &str) -> Option<T> {
+    let text = text
+        .trim_matches(|c: char| !c.is_alphabetic())
+        .to_lowercase();
+
+    table.iter()
+        .find(|&&(_, code, english, german, spellings)| {
+            text == code
+            || text == english.to_lowercase()
+            || text == german.to_lowercase()
+            || spellings.contains(&&*text)
+        })
+        .map(|&(value, ..)| value)
+}
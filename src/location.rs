@@ -0,0 +1,273 @@
+use serde::*;
+use serde::ser::SerializeStruct;
+use crate::labels::Row;
+use crate::common::{words, find_keyword};
+
+/// Where a bug or fish can be found, parsed from wiki texts like "River (Clifftop)" or "Flying near flowers".
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Location {
+    Known {
+        place: Place,
+        modifiers: Vec<Modifier>,
+    },
+    /// Text that could not be parsed
+    Other(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Place {
+    River,
+    Pond,
+    Sea,
+    Pier,
+    Air,
+    Flowers,
+    Trees,
+    PalmTrees,
+    Stumps,
+    Ground,
+    Rocks,
+    Beach,
+    BeachRocks,
+    WaterSurface,
+    Underground,
+    Villagers,
+    Trash,
+    RottenTurnips,
+    Snowballs,
+    Lights,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Modifier {
+    Clifftop,
+    Mouth,
+    Raining,
+    Flying,
+    Under,
+    Shaking,
+    Hitting,
+}
+
+/// (place, code, english label, german label, keywords)
+///
+/// Keywords are matched as whole words, allowing plurals.
+const PLACES: &[Row<Place>] = &[
+    (Place::RottenTurnips, "rotten_turnips", "Rotten turnips", "Verfaulte Rüben", &["rotten turnip", "rotten food"]),
+    (Place::PalmTrees, "palm_trees", "Palm trees", "Palmen", &["palm tree", "coconut tree"]),
+    (Place::BeachRocks, "beach_rocks", "Rocks at the beach", "Felsen am Strand", &["beach rock", "rocks at the beach", "coastal rock"]),
+    (Place::Stumps, "stumps", "Tree stumps", "Baumstümpfe", &["stump"]),
+    (Place::WaterSurface, "water_surface", "Water surface", "Wasseroberfläche", &["on river", "on pond", "surface of", "on water"]),
+    (Place::River, "river", "River", "Fluss", &["river"]),
+    (Place::Pond, "pond", "Pond", "Teich", &["pond"]),
+    (Place::Pier, "pier", "Pier", "Steg", &["pier"]),
+    (Place::Sea, "sea", "Sea", "Meer", &["sea", "ocean"]),
+    (Place::Flowers, "flowers", "Flowers", "Blumen", &["flower"]),
+    (Place::Trees, "trees", "Trees", "Bäume", &["tree"]),
+    (Place::Rocks, "rocks", "Rocks", "Felsen", &["rock"]),
+    (Place::Beach, "beach", "Beach", "Strand", &["beach", "shore", "shoreline", "sand"]),
+    (Place::Underground, "underground", "Underground", "Unterirdisch", &["underground"]),
+    (Place::Villagers, "villagers", "Villagers", "Nachbarn", &["villager"]),
+    (Place::Trash, "trash", "Trash", "Müll", &["trash", "garbage"]),
+    (Place::Snowballs, "snowballs", "Snowballs", "Schneebälle", &["snowball"]),
+    (Place::Lights, "lights", "Light sources", "Lichtquellen", &["light"]),
+    (Place::Ground, "ground", "Ground", "Boden", &["ground"]),
+    (Place::Air, "air", "Flying", "Fliegend", &["flying", "in the air"]),
+];
+
+/// Kind of water to fish in, grouping the places where fish are found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Water {
+    Fresh,
+    Salt,
+}
+
+/// (water, code, english label, german label, spellings)
+const WATERS: &[Row<Water>] = &[
+    (Water::Fresh, "fresh", "Freshwater", "Süßwasser", &[]),
+    (Water::Salt, "salt", "Saltwater", "Salzwasser", &[]),
+];
+
+/// (modifier, code, english label, german label, keywords)
+const MODIFIERS: &[Row<Modifier>] = &[
+    (Modifier::Clifftop, "clifftop", "Clifftop", "Klippe", &["clifftop", "cliff top"]),
+    (Modifier::Mouth, "mouth", "Mouth", "Mündung", &["mouth"]),
+    (Modifier::Raining, "raining", "Raining", "Bei Regen", &["rain", "raining", "rainy"]),
+    (Modifier::Flying, "flying", "Flying", "Fliegend", &["flying"]),
+    (Modifier::Under, "under", "Under", "Unter", &["under", "beneath"]),
+    (Modifier::Shaking, "shaking", "Shaking", "Schütteln", &["shaking", "shake"]),
+    (Modifier::Hitting, "hitting", "Hitting", "Schlagen", &["hitting", "hit"]),
+];
+
+impl Location {
+    /// Parses a wiki location, which may list several places like "River (Clifftop) & Pond".
+    pub fn parse_list(text: impl AsRef<str>) -> Vec<Self> {
+        let text = text.as_ref().trim();
+
+        if text.is_empty() {
+            return Vec::new();
+        }
+
+        text.split(" & ")
+            .map(Location::parse)
+            .collect()
+    }
+
+    /// The place is the one with the longest matching keyword, and of those the one named last,
+    /// like the flowers in "Flying near flowers". The other words are matched against modifiers.
+    pub fn parse(text: impl AsRef<str>) -> Self {
+        let text = text.as_ref().trim();
+        let mut words = words(text);
+
+        let found = PLACES.iter()
+            .flat_map(|&(place, _, _, _, keywords)| keywords.iter()
+                .filter_map(|keyword| find_keyword(&words, keyword))
+                .map(move |range| (place, range))
+            )
+            .max_by_key(|(_, range)| (range.len(), range.start));
+
+        let place = match found {
+            Some((place, range)) => {
+                for word in &mut words[range] {
+                    word.clear();
+                }
+
+                place
+            },
+            None => {
+                eprintln!("Unknown location '{}'", text);
+                return Location::Other(text.into());
+            },
+        };
+
+        let modifiers = MODIFIERS.iter()
+            .filter(|(_, _, _, _, keywords)| keywords.iter().any(|keyword| find_keyword(&words, keyword).is_some()))
+            .map(|&(modifier, ..)| modifier)
+            .collect();
+
+        Location::Known { place, modifiers }
+    }
+
+    pub fn place(&self) -> Option<Place> {
+        match self {
+            Location::Known { place, .. } => Some(*place),
+            Location::Other(_) => None,
+        }
+    }
+
+    /// Label in the given language, e.g. "River (Clifftop)".
+    /// Unparsed locations keep their original text.
+    pub fn label(&self, language: &str) -> Option<String> {
+        match self {
+            Location::Known { place, modifiers } if modifiers.is_empty() => place.label(language).map(<_>::into),
+            Location::Known { place, modifiers } => {
+                let modifiers = modifiers.iter()
+                    .map(|modifier| modifier.label(language))
+                    .collect::<Option<Vec<_>>>()?
+                    .join(", ");
+
+                Some(format!("{} ({})", place.label(language)?, modifiers))
+            },
+            Location::Other(text) => Some(text.clone()),
+        }
+    }
+}
+
+impl Place {
+    /// Finds the place of a code in the dumped data, e.g. "river".
+    pub fn from_code(code: &str) -> Option<Self> {
+        PLACES.iter()
+            .find(|&&(_, place_code, ..)| place_code == code)
+            .map(|&(place, ..)| place)
+    }
+
+    /// The kind of water of fishing spots, None for places on land.
+    pub fn water(self) -> Option<Water> {
+        match self {
+            Place::River | Place::Pond => Some(Water::Fresh),
+            Place::Sea | Place::Pier => Some(Water::Salt),
+            _ => None,
+        }
+    }
+}
+
+impl_labels!(Place, PLACES);
+impl_labels!(Modifier, MODIFIERS);
+impl_labels!(Water, WATERS);
+
+/// Serializes to `{"place": "river", "modifiers": ["clifftop"]}`,
+/// unparsed locations to `{"place": "other", "text": "..."}`.
+impl Serialize for Location {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Location::Known { place, modifiers } => {
+                let mut location = serializer.serialize_struct("Location", 2)?;
+                location.serialize_field("place", place)?;
+                location.serialize_field("modifiers", modifiers)?;
+                location.end()
+            },
+            Location::Other(text) => {
+                let mut location = serializer.serialize_struct("Location", 2)?;
+                location.serialize_field("place", "other")?;
+                location.serialize_field("text", text)?;
+                location.end()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn known(place: Place, modifiers: &[Modifier]) -> Location {
+        Location::Known { place, modifiers: modifiers.to_vec() }
+    }
+
+    #[test]
+    fn list_of_places() {
+        assert_eq!(
+            Location::parse_list("River (Clifftop) & Pond"),
+            vec![known(Place::River, &[Modifier::Clifftop]), known(Place::Pond, &[])],
+        );
+        assert_eq!(Location::parse_list(" "), vec![]);
+    }
+
+    #[test]
+    fn whole_words_only() {
+        assert_eq!(Location::parse("Underground"), known(Place::Underground, &[]));
+        assert_eq!(Location::parse("Under rocks"), known(Place::Rocks, &[Modifier::Under]));
+        assert_eq!(Location::parse("On the ground"), known(Place::Ground, &[]));
+        assert_eq!(Location::parse("Sea (when raining or snowing)"), known(Place::Sea, &[Modifier::Raining]));
+    }
+
+    #[test]
+    fn last_named_place_wins() {
+        assert_eq!(Location::parse("Flying"), known(Place::Air, &[]));
+        assert_eq!(Location::parse("Flying near flowers"), known(Place::Flowers, &[Modifier::Flying]));
+        assert_eq!(Location::parse("Flying near light sources"), known(Place::Lights, &[Modifier::Flying]));
+        assert_eq!(Location::parse("On tree stumps"), known(Place::Stumps, &[]));
+        assert_eq!(Location::parse("Shaking trees"), known(Place::Trees, &[Modifier::Shaking]));
+    }
+
+    #[test]
+    fn longer_keywords_win() {
+        assert_eq!(Location::parse("On palm trees"), known(Place::PalmTrees, &[]));
+        assert_eq!(Location::parse("Rocks at the beach"), known(Place::BeachRocks, &[]));
+        assert_eq!(Location::parse("On rivers and ponds"), known(Place::WaterSurface, &[]));
+        assert_eq!(Location::parse("River (Mouth)"), known(Place::River, &[Modifier::Mouth]));
+    }
+
+    #[test]
+    fn water_of_places() {
+        assert_eq!(Place::from_code("pond").and_then(Place::water), Some(Water::Fresh));
+        assert_eq!(Place::from_code("pier").and_then(Place::water), Some(Water::Salt));
+        assert_eq!(Place::WaterSurface.water(), None);
+        assert_eq!(Place::from_code("nowhere"), None);
+    }
+
+    #[test]
+    fn unknown_places() {
+        assert_eq!(Location::parse("Somewhere"), Location::Other("Somewhere".into()));
+    }
+}
@@ -2,6 +2,9 @@
 use serde_json as json;
 use common::*;
 use std::fs;
+use std::env;
+use std::path::Path;
+use itertools::Itertools;
 
 mod bugs;
 mod fossils;
@@ -9,14 +12,111 @@ mod fish;
 mod flowers;
 mod common;
 mod art;
+mod artworks;
 mod villagers;
 mod villagerdb;
+#[macro_use]
+mod labels;
+mod personality;
+mod species;
+mod game;
+mod location;
+mod details;
+mod spawn;
+mod rarity;
+mod prices;
+mod compatibility;
+mod ics;
+mod birthdays;
+mod critter_calendar;
+mod progress;
 mod id;
+mod dataset;
+mod site;
+mod android;
+mod merge;
+mod diff;
+mod pipeline;
+mod manifest;
+mod atlas;
+mod duplicates;
 
 const IMAGE_DL_FOLDER: &str = "images";
 const DATA_FOLDER: &str = "data";
+const PROGRESS_FILE: &str = "progress.json";
+
+const USAGE: &str = "\
+Usage:
+    ac_nh_wiki_dump [dump] [--sizes <px,...> | --android <px>] [--fit <fit>] [--format <format>]
+                                Dump the wiki into data/ and images/, rendering each image
+                                in the given sizes or as Android density buckets of <px> at mdpi,
+                                with fit stretch, contain or pad and format png, webp or jpeg[:<quality>]
+    ac_nh_wiki_dump site <dir>  Render a static catalog website into <dir>
+    ac_nh_wiki_dump strings <res_dir>
+                                Generate Android strings.xml files per locale into <res_dir>
+    ac_nh_wiki_dump merge <target.json> <category> <field,...> [<key>]
+                                Patch fields of dumped data into an existing JSON dataset,
+                                joining records by <key> (default: id)
+    ac_nh_wiki_dump diff <old_dir> <new_dir> [<out>]
+                                Write a changelog between two data directories
+                                to <out>.md and <out>.json (default: changelog)
+    ac_nh_wiki_dump atlas <dir> Pack the icons of each category into sprite sheets in <dir>
+    ac_nh_wiki_dump duplicates  Report near-identical and placeholder images
+    ac_nh_wiki_dump compat <id> <id>...
+                                Print the best and worst matching pairs of the given villagers
+    ac_nh_wiki_dump birthdays <out.ics> [--residents <file>] [--events]
+                                Export villager birthdays as iCalendar file, optionally only for
+                                the residents listed in <file> and with NPC birthdays and events
+    ac_nh_wiki_dump calendar <north|south> <out.ics> [--caught <file>] [--uncaught]
+                                Export when bugs and fish arrive, leave and show up during the day
+                                as iCalendar file, leaving out the critters listed in <file>
+                                or marked as caught in progress.json
+    ac_nh_wiki_dump progress status
+                                Print the completion of each category
+    ac_nh_wiki_dump progress <mark|unmark> <category> <status> <id|name>...
+                                Mark items as caught, found, bought, donated or bred
+    ac_nh_wiki_dump progress <import|export> <file>
+                                Merge progress from or copy it to another file
+";
 
 fn main() {
+    let args = env::args().skip(1).collect_vec();
+    let args = args.iter().map(String::as_str).collect_vec();
+
+    match *args {
+        [] => dump(&[]),
+        ["dump", ref options @ ..] => dump(options),
+        ["site", out_dir] => site::generate(DATA_FOLDER, IMAGE_DL_FOLDER, out_dir).expect("site"),
+        ["strings", res_dir] => android::export_strings(DATA_FOLDER, res_dir).expect("strings"),
+        ["merge", target, category, fields] => merge(target, category, fields, "id"),
+        ["merge", target, category, fields, key] => merge(target, category, fields, key),
+        ["diff", old_dir, new_dir] => diff::diff(old_dir, new_dir, "changelog").expect("diff"),
+        ["diff", old_dir, new_dir, out] => diff::diff(old_dir, new_dir, out).expect("diff"),
+        ["atlas", out_dir] => atlas::generate(DATA_FOLDER, IMAGE_DL_FOLDER, out_dir).expect("atlas"),
+        ["duplicates"] => {
+            duplicates::detect(IMAGE_DL_FOLDER).expect("duplicates");
+        },
+        ["compat", ref ids @ ..] if ids.len() >= 2 => compat(ids),
+        ["birthdays", out, ref options @ ..] => birthdays(out, options),
+        ["calendar", hemisphere, out, ref options @ ..] => calendar(hemisphere, out, options),
+        ["progress", "status"] => progress::Progress::load(PROGRESS_FILE)
+            .and_then(|progress| progress.print_status())
+            .expect("progress"),
+        ["progress", "mark", category, status, ref items @ ..] => mark_progress(true, category, status, items),
+        ["progress", "unmark", category, status, ref items @ ..] => mark_progress(false, category, status, items),
+        ["progress", "import", file] => import_progress(file),
+        ["progress", "export", file] => progress::Progress::load(PROGRESS_FILE)
+            .and_then(|progress| progress.save(file))
+            .expect("export"),
+        _ => {
+            eprint!("{}", USAGE);
+            std::process::exit(1);
+        },
+    }
+}
+
+fn dump(args: &[&str]) {
+    let options = download_options(args);
     fs::create_dir_all(DATA_FOLDER).unwrap();
 
     // ### Bugs ###
@@ -33,6 +133,9 @@ fn main() {
     // let fossils = fossils::fetch_all().expect("fossils");
     // let json_fossils = json::to_string_pretty(&fossils).unwrap();
     // fs::write("data/fossils.json", json_fossils).unwrap();
+    // let fossil_sets = fossils::sets(&fossils);
+    // let json_fossil_sets = json::to_string_pretty(&fossil_sets).unwrap();
+    // fs::write("data/fossil_sets.json", json_fossil_sets).unwrap();
 
     // // ### Flowers ###
     // let flowers = flowers::fetch_all().expect("flowers");
@@ -40,7 +143,9 @@ fn main() {
     // fs::write("data/flowers.json", json_flowers).unwrap();
 
     // ### Art ###
-    // let art = art::fetch_all().expect("art");
+    // let mut art = art::fetch_all().expect("art");
+    // download_images_with(&art, IMAGE_DL_FOLDER, options.clone()).unwrap();
+    // art::compute_diff_regions(&mut art, IMAGE_DL_FOLDER).unwrap();
     // let json_art = json::to_string_pretty(&art).unwrap();
     // fs::write("data/art.json", json_art).unwrap();
 
@@ -49,24 +154,121 @@ fn main() {
     let json_villagers = json::to_string_pretty(&villagers).unwrap();
     fs::write("data/villagers.json", json_villagers).unwrap();
 
-    // let old_fish_json = include_bytes!("../../../Downloads/ac_res/res/raw/fish.json");
-    // let mut old_fishes: Vec<json::Value> = json::from_slice(old_fish_json).unwrap();
-
-    // for old_fish in &mut old_fishes {
-    //     let old_fish = old_fish.as_object_mut().unwrap();
-    //     let id = old_fish["id"].as_i64().unwrap();
-    //     let fish = fishes.iter().find(|fish| fish.id == id as usize).unwrap();
-    //     let shadow = serde_json::to_value(&fish.shadow).unwrap();
-    //     old_fish.insert("shadow".into(), shadow);
-    // }
-
-    // let old_fishes = json::to_string_pretty(&old_fishes).unwrap();
-    // fs::write("/tmp/fish.json", old_fishes).unwrap();
-
-    // download_images(bugs, IMAGE_DL_FOLDER).unwrap();
-    // download_images(fish, IMAGE_DL_FOLDER).unwrap();
-    // download_images(fossils, IMAGE_DL_FOLDER).unwrap();
-    // download_images(flowers, IMAGE_DL_FOLDER).unwrap();
-    // download_images(art, IMAGE_DL_FOLDER).unwrap();
-    download_images(villagers, IMAGE_DL_FOLDER).unwrap();
+    // download_images_with(bugs, IMAGE_DL_FOLDER, options.clone()).unwrap();
+    // download_images_with(fish, IMAGE_DL_FOLDER, options.clone()).unwrap();
+    // download_images_with(fossils, IMAGE_DL_FOLDER, options.clone()).unwrap();
+    // download_images_with(flowers, IMAGE_DL_FOLDER, options.clone()).unwrap();
+    download_images_with(villagers, IMAGE_DL_FOLDER, options).unwrap();
+}
+
+fn download_options(args: &[&str]) -> DownloadOptions {
+    let mut options = DownloadOptions::default();
+    let mut args = args.iter();
+
+    while let Some(&arg) = args.next() {
+        let overrides = &mut options.pipeline;
+
+        match arg {
+            "--sizes" => {
+                let sizes = args.next().expect("missing sizes")
+                    .split(',')
+                    .map(|size| size.trim().parse().expect("invalid size"))
+                    .collect_vec();
+                overrides.variants = Some(pipeline::Pipeline::sizes(&sizes).variants);
+            },
+            "--android" => {
+                let base = args.next().expect("missing base size").parse().expect("invalid base size");
+                overrides.variants = Some(pipeline::Pipeline::android_densities(base).variants);
+            },
+            "--fit" => overrides.fit = Some(pipeline::Fit::parse(args.next().expect("missing fit")).expect("fit")),
+            "--format" => overrides.format = Some(pipeline::OutputFormat::parse(args.next().expect("missing format")).expect("format")),
+            arg => panic!("Unknown option '{}'", arg),
+        }
+    }
+
+    options
+}
+
+fn merge(target: &str, category: &str, fields: &str, key: &str) {
+    let fields = fields.split(',').map(str::trim).collect_vec();
+    merge::merge(DATA_FOLDER, target, category, &fields, key).expect("merge");
+}
+
+fn birthdays(out: &str, args: &[&str]) {
+    let mut options = birthdays::Options {
+        residents: None,
+        events: false,
+    };
+    let mut args = args.iter();
+
+    while let Some(&arg) = args.next() {
+        match arg {
+            "--residents" => options.residents = Some(Path::new(args.next().expect("missing residents file"))),
+            "--events" => options.events = true,
+            arg => panic!("Unknown option '{}'", arg),
+        }
+    }
+
+    birthdays::export(DATA_FOLDER, out, &options).expect("birthdays");
+}
+
+fn calendar(hemisphere: &str, out: &str, args: &[&str]) {
+    let mut options = critter_calendar::Options {
+        hemisphere: critter_calendar::Hemisphere::parse(hemisphere).expect("hemisphere"),
+        exclude: Default::default(),
+    };
+    let mut args = args.iter();
+
+    while let Some(&arg) = args.next() {
+        match arg {
+            "--caught" => {
+                let caught = args.next().expect("missing caught file");
+                options.exclude.extend(critter_calendar::read_critter_list(DATA_FOLDER, caught).expect("caught"));
+            },
+            "--uncaught" => {
+                let progress = progress::Progress::load(PROGRESS_FILE).expect("progress");
+
+                for &category in &["bugs", "fish"] {
+                    let caught = progress.items(category, progress::Status::Caught);
+                    options.exclude.extend(caught.into_iter().map(|id| (category, id as u64)));
+                }
+            },
+            arg => panic!("Unknown option '{}'", arg),
+        }
+    }
+
+    critter_calendar::export(DATA_FOLDER, out, &options).expect("calendar");
+}
+
+fn mark_progress(mark: bool, category: &str, status: &str, items: &[&str]) {
+    let mut progress = progress::Progress::load(PROGRESS_FILE).expect("progress");
+    let status = progress::parse_status(status).expect("status");
+
+    for item in items {
+        let id = progress::resolve(category, item).expect("item");
+
+        if mark {
+            progress.mark(category, status, id).expect("mark");
+        } else {
+            progress.unmark(category, status, id).expect("unmark");
+        }
+    }
+
+    progress.save(PROGRESS_FILE).expect("save progress");
+}
+
+fn import_progress(file: &str) {
+    let mut progress = progress::Progress::load(PROGRESS_FILE).expect("progress");
+
+    progress::Progress::import(file)
+        .and_then(|imported| progress.merge(imported))
+        .expect("import");
+    progress.save(PROGRESS_FILE).expect("save progress");
+}
+
+fn compat(ids: &[&str]) {
+    let ids = ids.iter()
+        .map(|id| id.parse().unwrap_or_else(|_| panic!("Invalid villager id '{}'", id)))
+        .collect_vec();
+    compatibility::report(DATA_FOLDER, &ids).expect("compat");
 }
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::fs;
+use serde::*;
+use serde_json as json;
+use sha2::{Sha256, Digest};
+use failure::{Fallible, format_err};
+use crate::common::write_atomically;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Records which source produced which files in the image folder.
+/// Keyed by `File::name`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Manifest {
+    pub entries: BTreeMap<String, Entry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub id: usize,
+    pub category: String,
+    pub url: String,
+    /// Hash of the downloaded source image, before any processing
+    pub source_sha256: Option<String>,
+    /// Validators of the source response, used to check cached files for upstream changes
+    #[serde(default)]
+    pub etag: Option<String>,
+    #[serde(default)]
+    pub last_modified: Option<String>,
+    pub outputs: Vec<Output>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Output {
+    pub path: String,
+    pub width: u32,
+    pub height: u32,
+    pub bytes: u64,
+    pub sha256: String,
+}
+
+impl Manifest {
+    /// Loads the manifest of `dir`, or an empty one if there is none yet.
+    pub fn load(dir: impl AsRef<Path>) -> Fallible<Self> {
+        let path = dir.as_ref().join(MANIFEST_FILE);
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let data = fs::read(&path)?;
+        let manifest = json::from_slice(&data)
+            .map_err(|err| format_err!("Failed to parse '{}': {}", path.display(), err))?;
+
+        Ok(manifest)
+    }
+
+    pub fn save(&self, dir: impl AsRef<Path>) -> Fallible<()> {
+        let path = dir.as_ref().join(MANIFEST_FILE);
+        let data = json::to_string_pretty(self)?;
+
+        write_atomically(path, data)
+    }
+
+    /// Path of the largest output of `name`, so it follows the sizes and format the images were
+    /// dumped with. Falls back to the default PNG for files that are not recorded.
+    pub fn image_path(&self, name: &str) -> String {
+        self.entries.get(name)
+            .and_then(|entry| entry.outputs.iter().max_by_key(|output| output.width * output.height))
+            .map(|output| output.path.clone())
+            .unwrap_or_else(|| format!("{}.png", name))
+    }
+
+    /// The entry of `name`, if it was recorded for the same URL.
+    pub fn entry(&self, name: &str, url: &str) -> Option<&Entry> {
+        self.entries.get(name).filter(|entry| entry.url == url)
+    }
+
+    /// Adds or replaces the entry of `name`.
+    /// Keeps the previous source hash and validators if the file was not downloaded again and still has the same URL.
+    pub fn update(&mut self, name: String, mut entry: Entry) {
+        if let Some(old_entry) = self.entries.get(&name) {
+            if entry.url == old_entry.url {
+                if entry.source_sha256.is_none() {
+                    entry.source_sha256 = old_entry.source_sha256.clone();
+                }
+
+                if entry.etag.is_none() && entry.last_modified.is_none() {
+                    entry.etag = old_entry.etag.clone();
+                    entry.last_modified = old_entry.last_modified.clone();
+                }
+            }
+
+            if let (Some(old_hash), Some(new_hash)) = (&old_entry.source_sha256, &entry.source_sha256) {
+                if old_hash != new_hash {
+                    println!("Upstream image of '{}' changed: {}", name, entry.url);
+                }
+            }
+        }
+
+        self.entries.insert(name, entry);
+    }
+}
+
+pub fn sha256(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
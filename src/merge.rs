@@ -0,0 +1,92 @@
+use std::path::Path;
+use std::fs;
+use serde_json::{self as json, Value};
+use failure::{Fallible, format_err, bail};
+use crate::common::*;
+use crate::dataset;
+
+/// Patches `fields` of the freshly dumped `category` into the external JSON dataset at `target`.
+/// Records are joined by `key`, which has to exist in both datasets.
+pub fn merge(
+    data_dir: impl AsRef<Path>,
+    target: impl AsRef<Path>,
+    category: &str,
+    fields: &[&str],
+    key: &str,
+) -> Fallible<()> {
+    let target = target.as_ref();
+    let category = dataset::category(category)?;
+    let sources = dataset::load(data_dir, category)?;
+
+    if sources.is_empty() {
+        bail!("No dumped data for category '{}'", category.name);
+    }
+
+    let data = fs::read(target)?;
+    let mut records: Vec<Value> = json::from_slice(&data)
+        .map_err(|err| format_err!("Failed to parse '{}': {}", target.display(), err))?;
+
+    let mut unmatched_records = Vec::new();
+    let mut matched_sources = vec![false; sources.len()];
+    let mut patched = 0;
+
+    for (index, record) in records.iter_mut().enumerate() {
+        let record = record.as_object_mut()
+            .ok_or_else(|| format_err!("Record #{} is not an object", index))?;
+
+        let record_key = match record.get(key) {
+            Some(record_key) => record_key.clone(),
+            None => {
+                unmatched_records.push(format!("#{} (no '{}')", index, key));
+                continue;
+            },
+        };
+
+        let source_index = sources.iter()
+            .position(|source| source.get(key) == Some(&record_key));
+        let source_index = match source_index {
+            Some(source_index) => source_index,
+            None => {
+                unmatched_records.push(format!("#{} ({} = {})", index, key, record_key));
+                continue;
+            },
+        };
+
+        matched_sources[source_index] = true;
+
+        for &field in fields {
+            match sources[source_index].get(field) {
+                Some(value) => {
+                    record.insert(field.into(), value.clone());
+                },
+                None => eprintln!("Source {} = {} has no field '{}'", key, record_key, field),
+            }
+        }
+
+        patched += 1;
+    }
+
+    let unmatched_sources = sources.iter()
+        .zip(&matched_sources)
+        .filter(|(_, &matched)| !matched)
+        .map(|(source, _)| source.get(key).map(Value::to_string).unwrap_or_else(|| "?".into()))
+        .collect::<Vec<_>>();
+
+    println!("Patched {} of {} records in '{}'", patched, records.len(), target.display());
+
+    if !unmatched_records.is_empty() {
+        println!("Records without a matching {} entry:", category.name);
+        for record in &unmatched_records {
+            println!("    {}", record);
+        }
+    }
+
+    if !unmatched_sources.is_empty() {
+        println!("Dumped {} not present in target ({} = …): {}", category.name, key, unmatched_sources.join(", "));
+    }
+
+    let records = json::to_string_pretty(&records)?;
+    write_atomically(target, records)?;
+
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use crate::labels::{self, Row};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Personality {
+    Normal,
+    Peppy,
+    Snooty,
+    Sisterly,
+    Lazy,
+    Jock,
+    Cranky,
+    Smug,
+}
+
+/// (personality, code, english label, german label, further spellings)
+const PERSONALITIES: &[Row<Personality>] = &[
+    (Personality::Normal, "normal", "Normal", "Normal", &[]),
+    (Personality::Peppy, "peppy", "Peppy", "Aufgedreht", &["fröhlich", "quirlig"]),
+    (Personality::Snooty, "snooty", "Snooty", "Hochnäsig", &["zickig", "eitel"]),
+    (Personality::Sisterly, "sisterly", "Sisterly", "Schwesterlich", &["uchi", "big sister", "burschikos"]),
+    (Personality::Lazy, "lazy", "Lazy", "Faul", &["gemütlich"]),
+    (Personality::Jock, "jock", "Jock", "Sportlich", &["athletisch"]),
+    (Personality::Cranky, "cranky", "Cranky", "Mürrisch", &["griesgrämig", "grantig"]),
+    (Personality::Smug, "smug", "Smug", "Schnöselig", &["eingebildet", "snobistisch", "versnobt", "arrogant"]),
+];
+
+impl_labels!(Personality, PERSONALITIES);
+
+impl Personality {
+    /// Parses english and german spellings, ignoring case and surrounding punctuation.
+    pub fn parse(text: impl AsRef<str>) -> Option<Self> {
+        labels::parse(PERSONALITIES, text.as_ref())
+    }
+}
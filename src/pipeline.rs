@@ -0,0 +1,338 @@
+use std::io::Cursor;
+use image::{DynamicImage, GenericImageView, ImageFormat, Rgba, RgbaImage, imageops::{self, FilterType}};
+use failure::{Fallible, format_err};
+
+/// Describes which image files are produced from one downloaded source image.
+#[derive(Debug, Clone)]
+pub struct Pipeline {
+    /// Normalizes the source image before the variants are rendered
+    pub trim: Option<Trim>,
+    pub variants: Vec<Variant>,
+}
+
+/// Crops the source to the bounding box of its visible pixels
+/// and centers it on a square transparent canvas.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Trim {
+    /// Pixels with an alpha value up to this are considered empty
+    pub alpha_threshold: u8,
+    /// Margin around the subject, relative to the longer side of the subject
+    pub margin: f32,
+}
+
+/// One output file of a pipeline.
+/// Its file name is `{prefix}{name}{suffix}.{extension}`.
+#[derive(Debug, Clone)]
+pub struct Variant {
+    pub prefix: String,
+    pub suffix: String,
+    pub width: u32,
+    pub height: u32,
+    pub fit: Fit,
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fit {
+    /// Scale to exactly the target size, ignoring the aspect ratio
+    Stretch,
+    /// Scale to fit into the target size, preserving the aspect ratio
+    Contain,
+    /// Like `Contain`, but centered on a transparent canvas of the target size
+    Pad,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Png,
+    Jpeg { quality: u8 },
+    WebP,
+}
+
+/// Changes applied to the pipeline of every downloaded file, e.g. from the command line.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    /// Replaces the variants, each keeping the fit of the file's own pipeline
+    pub variants: Option<Vec<Variant>>,
+    pub fit: Option<Fit>,
+    pub format: Option<OutputFormat>,
+}
+
+/// Android density buckets and their scale relative to mdpi.
+const ANDROID_DENSITIES: &[(&str, f32)] = &[
+    ("mdpi", 1.0),
+    ("hdpi", 1.5),
+    ("xhdpi", 2.0),
+    ("xxhdpi", 3.0),
+    ("xxxhdpi", 4.0),
+];
+
+impl Default for Pipeline {
+    /// A single PNG of exactly 256×256.
+    fn default() -> Self {
+        Self {
+            trim: None,
+            variants: vec![Variant::new(256, 256)],
+        }
+    }
+}
+
+impl Pipeline {
+    /// One square variant per size, named `{name}_{size}`.
+    pub fn sizes(sizes: &[u32]) -> Self {
+        let variants = sizes.iter()
+            .map(|&size| Variant {
+                suffix: format!("_{}", size),
+                ..Variant::new(size, size)
+            })
+            .collect();
+
+        Self { trim: None, variants }
+    }
+
+    /// One square variant per Android density bucket, named `drawable-{density}/{name}`.
+    /// `base_size` is the size in mdpi.
+    pub fn android_densities(base_size: u32) -> Self {
+        let variants = ANDROID_DENSITIES.iter()
+            .map(|&(density, scale)| {
+                let size = (base_size as f32 * scale).round() as u32;
+
+                Variant {
+                    prefix: format!("drawable-{}/", density),
+                    ..Variant::new(size, size)
+                }
+            })
+            .collect();
+
+        Self { trim: None, variants }
+    }
+
+    pub fn with_fit(mut self, fit: Fit) -> Self {
+        for variant in &mut self.variants {
+            variant.fit = fit;
+        }
+
+        self
+    }
+
+    pub fn with_trim(mut self, trim: Trim) -> Self {
+        self.trim = Some(trim);
+        self
+    }
+
+    pub fn with_format(mut self, format: OutputFormat) -> Self {
+        for variant in &mut self.variants {
+            variant.format = format;
+        }
+
+        self
+    }
+
+    pub fn with_overrides(mut self, overrides: &Overrides) -> Self {
+        if let Some(variants) = &overrides.variants {
+            let fit = self.variants.first().map(|variant| variant.fit).unwrap_or(Fit::Stretch);
+            self.variants = variants.clone();
+            self = self.with_fit(fit);
+        }
+
+        if let Some(fit) = overrides.fit {
+            self = self.with_fit(fit);
+        }
+
+        if let Some(format) = overrides.format {
+            self = self.with_format(format);
+        }
+
+        self
+    }
+
+    /// Decodes `source` and renders every variant.
+    pub fn process(&self, source: &[u8]) -> Fallible<Vec<(&Variant, Vec<u8>)>> {
+        let mut source = image::load_from_memory(source)?;
+
+        if let Some(trim) = &self.trim {
+            source = trim.apply(&source);
+        }
+
+        self.variants.iter()
+            .map(|variant| Ok((variant, variant.render(&source)?)))
+            .collect()
+    }
+}
+
+impl Default for Trim {
+    fn default() -> Self {
+        Self {
+            alpha_threshold: 8,
+            margin: 0.05,
+        }
+    }
+}
+
+impl Trim {
+    pub fn apply(&self, source: &DynamicImage) -> DynamicImage {
+        let source = source.to_rgba8();
+        let visible = source.enumerate_pixels()
+            .filter(|(_, _, pixel)| pixel[3] > self.alpha_threshold)
+            .map(|(x, y, _)| (x, y));
+
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (u32::MAX, u32::MAX, 0, 0);
+
+        for (x, y) in visible {
+            min_x = min_x.min(x);
+            min_y = min_y.min(y);
+            max_x = max_x.max(x);
+            max_y = max_y.max(y);
+        }
+
+        // Nothing visible, keep the image as it is
+        if min_x > max_x {
+            return DynamicImage::ImageRgba8(source);
+        }
+
+        let width = max_x - min_x + 1;
+        let height = max_y - min_y + 1;
+        let subject = imageops::crop_imm(&source, min_x, min_y, width, height).to_image();
+
+        let side = width.max(height);
+        let side = side + 2 * (side as f32 * self.margin).round() as u32;
+        let mut canvas = RgbaImage::from_pixel(side, side, Rgba([0, 0, 0, 0]));
+
+        imageops::overlay(&mut canvas, &subject, (side - width) / 2, (side - height) / 2);
+
+        DynamicImage::ImageRgba8(canvas)
+    }
+}
+
+impl Variant {
+    /// A PNG stretched to the given size without prefix or suffix.
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            prefix: String::new(),
+            suffix: String::new(),
+            width,
+            height,
+            fit: Fit::Stretch,
+            format: OutputFormat::Png,
+        }
+    }
+
+    pub fn file_name(&self, name: &str) -> String {
+        format!("{}{}{}.{}", self.prefix, name, self.suffix, self.format.extension())
+    }
+
+    pub fn render(&self, source: &DynamicImage) -> Fallible<Vec<u8>> {
+        let image = self.resize(source);
+
+        self.format.encode(&image)
+    }
+
+    fn resize(&self, source: &DynamicImage) -> DynamicImage {
+        if source.dimensions() == (self.width, self.height) {
+            return source.clone();
+        }
+
+        match self.fit {
+            Fit::Stretch => source.resize_exact(self.width, self.height, FilterType::Lanczos3),
+            Fit::Contain => source.resize(self.width, self.height, FilterType::Lanczos3),
+            Fit::Pad => {
+                let resized = source.resize(self.width, self.height, FilterType::Lanczos3);
+                let mut canvas = RgbaImage::from_pixel(self.width, self.height, Rgba([0, 0, 0, 0]));
+                let x = (self.width - resized.width()) / 2;
+                let y = (self.height - resized.height()) / 2;
+
+                imageops::overlay(&mut canvas, &resized.to_rgba8(), x, y);
+
+                DynamicImage::ImageRgba8(canvas)
+            },
+        }
+    }
+}
+
+impl Fit {
+    pub fn parse(text: &str) -> Fallible<Self> {
+        match text {
+            "stretch" => Ok(Fit::Stretch),
+            "contain" => Ok(Fit::Contain),
+            "pad" => Ok(Fit::Pad),
+            _ => Err(format_err!("Unknown fit '{}'", text)),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Parses "png", "webp", "jpeg" or "jpeg:{quality}".
+    pub fn parse(text: &str) -> Fallible<Self> {
+        match text.split(':').collect::<Vec<_>>().as_slice() {
+            ["png"] => Ok(OutputFormat::Png),
+            ["webp"] => Ok(OutputFormat::WebP),
+            ["jpeg"] | ["jpg"] => Ok(OutputFormat::Jpeg { quality: 90 }),
+            ["jpeg", quality] | ["jpg", quality] => {
+                let quality = quality.parse()
+                    .map_err(|_| format_err!("Invalid JPEG quality '{}'", quality))?;
+
+                Ok(OutputFormat::Jpeg { quality })
+            },
+            _ => Err(format_err!("Unknown format '{}'", text)),
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Png => "png",
+            OutputFormat::Jpeg { .. } => "jpg",
+            OutputFormat::WebP => "webp",
+        }
+    }
+
+    pub fn encode(&self, image: &DynamicImage) -> Fallible<Vec<u8>> {
+        let mut target = Vec::new();
+
+        match *self {
+            OutputFormat::Png => image.write_to(&mut target, ImageFormat::Png)?,
+            OutputFormat::Jpeg { quality } => {
+                // JPEG has no alpha channel, so transparent areas become white
+                let (width, height) = image.dimensions();
+                let mut flattened = RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+                imageops::overlay(&mut flattened, &image.to_rgba8(), 0, 0);
+                let flattened = DynamicImage::ImageRgba8(flattened).to_rgb8();
+
+                image::jpeg::JpegEncoder::new_with_quality(&mut target, quality)
+                    .encode(&flattened, width, height, image::ColorType::Rgb8)?;
+            },
+            OutputFormat::WebP => {
+                let image = image.to_rgba8();
+
+                image_webp::WebPEncoder::new(&mut target)
+                    .encode(&image, image.width(), image.height(), image_webp::ColorType::Rgba8)
+                    .map_err(|err| format_err!("Failed to encode WebP: {}", err))?;
+            },
+        }
+
+        Ok(target)
+    }
+
+    /// Decodes `data` to make sure it is a complete image of this format.
+    /// Returns its dimensions.
+    pub fn validate(&self, data: &[u8]) -> Fallible<(u32, u32)> {
+        match self {
+            OutputFormat::Png | OutputFormat::Jpeg { .. } => {
+                let image = image::load_from_memory(data)?;
+
+                Ok(image.dimensions())
+            },
+            OutputFormat::WebP => {
+                let mut decoder = image_webp::WebPDecoder::new(Cursor::new(data))
+                    .map_err(|err| format_err!("Invalid WebP: {}", err))?;
+                let size = decoder.output_buffer_size()
+                    .ok_or_else(|| format_err!("WebP image too large"))?;
+                let mut buffer = vec![0; size];
+
+                decoder.read_image(&mut buffer)
+                    .map_err(|err| format_err!("Invalid WebP: {}", err))?;
+
+                Ok(decoder.dimensions())
+            },
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use serde::*;
+
+/// Flick and C.J. pay one and a half times the regular price.
+const SPECIAL_BUYER_FACTOR: (i32, i32) = (3, 2);
+
+/// What an item sells and buys for. Unknown prices are `None`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Prices {
+    /// Price at Nook's Cranny
+    pub sell: Option<i32>,
+    /// Price at the category's special buyer (Flick for bugs, C.J. for fish)
+    pub special: Option<i32>,
+    /// Price to buy the item, where it can be bought, e.g. art from Redd
+    pub buy: Option<i32>,
+}
+
+impl Prices {
+    pub fn sell(sell: Option<i32>) -> Self {
+        Self {
+            sell,
+            ..Self::default()
+        }
+    }
+
+    /// Uses the scraped special buyer price, or computes it from the sell price.
+    pub fn with_special_buyer(mut self, scraped: Option<i32>) -> Self {
+        let (numerator, denominator) = SPECIAL_BUYER_FACTOR;
+
+        self.special = scraped.or_else(|| Some(self.sell? * numerator / denominator));
+
+        if let (Some(scraped), Some(sell)) = (scraped, self.sell) {
+            if scraped != sell * numerator / denominator {
+                eprintln!("Special buyer price {} does not match sell price {}", scraped, sell);
+            }
+        }
+
+        self
+    }
+
+    pub fn with_buy(mut self, buy: Option<i32>) -> Self {
+        self.buy = buy;
+        self
+    }
+}
@@ -0,0 +1,211 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use std::fs;
+use serde::*;
+use serde_json as json;
+use failure::{Fallible, bail, format_err};
+use itertools::Itertools;
+use crate::common::write_atomically;
+use crate::id;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all="lowercase")]
+pub enum Status {
+    Caught,
+    Found,
+    Bought,
+    Donated,
+    Bred,
+}
+
+/// (category, statuses that can be tracked)
+/// The first status is how an item is obtained, donating implies it.
+const TRACKED: &[(&str, &[Status])] = &[
+    ("bugs", &[Status::Caught, Status::Donated]),
+    ("fish", &[Status::Caught, Status::Donated]),
+    ("fossils", &[Status::Found, Status::Donated]),
+    ("art", &[Status::Bought, Status::Donated]),
+    ("flowers", &[Status::Bred]),
+];
+
+/// Which items of each category have been caught, donated etc.
+/// Items are keyed by the registry IDs of `id.rs`, which for fossils differ
+/// from the positional IDs in the dumped data and image names.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Progress {
+    pub categories: BTreeMap<String, BTreeMap<Status, BTreeSet<usize>>>,
+}
+
+impl Progress {
+    /// Loads the progress at `path`, or an empty one if there is none yet.
+    pub fn load(path: impl AsRef<Path>) -> Fallible<Self> {
+        let path = path.as_ref();
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let data = fs::read(path)?;
+        let progress: Self = json::from_slice(&data)
+            .map_err(|err| format_err!("Failed to parse '{}': {}", path.display(), err))?;
+
+        progress.validate()?;
+
+        Ok(progress)
+    }
+
+    /// Loads the progress exported to `path`, which unlike `load` has to exist.
+    pub fn import(path: impl AsRef<Path>) -> Fallible<Self> {
+        let path = path.as_ref();
+
+        if !path.exists() {
+            bail!("'{}' does not exist", path.display());
+        }
+
+        Self::load(path)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Fallible<()> {
+        write_atomically(path, json::to_string_pretty(self)?)
+    }
+
+    pub fn items(&self, category: &str, status: Status) -> BTreeSet<usize> {
+        self.categories
+            .get(category)
+            .and_then(|statuses| statuses.get(&status))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn mark(&mut self, category: &str, status: Status, id: usize) -> Fallible<()> {
+        let statuses = statuses(category)?;
+        check(category, status, id)?;
+
+        let items = self.categories.entry(category.into()).or_default();
+        items.entry(status).or_default().insert(id);
+
+        // Donating implies owning the item
+        if status == Status::Donated {
+            items.entry(statuses[0]).or_default().insert(id);
+        }
+
+        Ok(())
+    }
+
+    pub fn unmark(&mut self, category: &str, status: Status, id: usize) -> Fallible<()> {
+        let statuses = statuses(category)?;
+        check(category, status, id)?;
+
+        let items = match self.categories.get_mut(category) {
+            Some(items) => items,
+            None => return Ok(()),
+        };
+        let unmarked = if status == statuses[0] {
+            vec![status, Status::Donated]
+        } else {
+            vec![status]
+        };
+
+        for status in unmarked {
+            if let Some(ids) = items.get_mut(&status) {
+                ids.remove(&id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Adds all progress of `other`.
+    pub fn merge(&mut self, other: Progress) -> Fallible<()> {
+        for (category, statuses) in other.categories {
+            for (status, ids) in statuses {
+                for id in ids {
+                    self.mark(&category, status, id)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate(&self) -> Fallible<()> {
+        for (category, statuses) in &self.categories {
+            for (&status, ids) in statuses {
+                for &id in ids {
+                    check(category, status, id)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prints the completion of each category.
+    pub fn print_status(&self) -> Fallible<()> {
+        for &(category, statuses) in TRACKED {
+            let total = registry(category)?.len();
+
+            let counts = statuses.iter()
+                .map(|&status| {
+                    let count = self.items(category, status).len();
+                    let percentage = 100. * count as f32 / total as f32;
+
+                    format!("{} {}/{} ({:.0}%)", name(status), count, total, percentage)
+                })
+                .join(", ");
+
+            println!("{:<8} {}", category, counts);
+        }
+
+        Ok(())
+    }
+}
+
+/// Resolves an item given by ID or english name.
+pub fn resolve(category: &str, item: &str) -> Fallible<usize> {
+    let registry = registry(category)?;
+
+    let id = match item.parse::<usize>() {
+        Ok(id) if id < registry.len() => Some(id),
+        Ok(_) => None,
+        Err(_) => id::lookup(category, item),
+    };
+
+    id.ok_or_else(|| format_err!("Unknown {} item '{}'", category, item))
+}
+
+pub fn parse_status(text: &str) -> Fallible<Status> {
+    json::from_value(json::Value::String(text.to_lowercase()))
+        .map_err(|_| format_err!("Unknown status '{}'", text))
+}
+
+fn name(status: Status) -> String {
+    json::to_value(status)
+        .ok()
+        .and_then(|status| status.as_str().map(<_>::into))
+        .unwrap_or_default()
+}
+
+fn statuses(category: &str) -> Fallible<&'static [Status]> {
+    TRACKED.iter()
+        .find(|&&(other, _)| other == category)
+        .map(|&(_, statuses)| statuses)
+        .ok_or_else(|| format_err!("Progress of '{}' is not tracked", category))
+}
+
+fn registry(category: &str) -> Fallible<Vec<&'static str>> {
+    id::registry(category).ok_or_else(|| format_err!("Unknown category '{}'", category))
+}
+
+fn check(category: &str, status: Status, id: usize) -> Fallible<()> {
+    if !statuses(category)?.contains(&status) {
+        bail!("{} can not be marked as {}", category, name(status));
+    }
+
+    if id >= registry(category)?.len() {
+        bail!("Unknown {} id {}", category, id);
+    }
+
+    Ok(())
+}
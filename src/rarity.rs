@@ -0,0 +1,62 @@
+use serde::*;
+
+/// How rarely a critter shows up, as rated by the wiki.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all="snake_case")]
+pub enum Rarity {
+    Common,
+    FairlyCommon,
+    Uncommon,
+    Scarce,
+    Rare,
+    VeryRare,
+    UltraRare,
+}
+
+/// (rarity, spellings)
+///
+/// Spellings are lowercase, with hyphens replaced by spaces.
+const RARITIES: &[(Rarity, &[&str])] = &[
+    (Rarity::Common, &["common", "very common"]),
+    (Rarity::FairlyCommon, &["fairly common", "somewhat common"]),
+    (Rarity::Uncommon, &["uncommon"]),
+    (Rarity::Scarce, &["scarce"]),
+    (Rarity::Rare, &["rare"]),
+    (Rarity::VeryRare, &["very rare"]),
+    (Rarity::UltraRare, &["ultra rare", "extremely rare"]),
+];
+
+impl Rarity {
+    /// Parses ratings like "Fairly common" or "Ultra-rare", ignoring case.
+    pub fn parse(text: impl AsRef<str>) -> Option<Self> {
+        let text = text.as_ref()
+            .to_lowercase()
+            .replace('-', " ")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        RARITIES.iter()
+            .find(|(_, spellings)| spellings.contains(&text.as_str()))
+            .map(|&(rarity, _)| rarity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spellings() {
+        assert_eq!(Rarity::parse("Common"), Some(Rarity::Common));
+        assert_eq!(Rarity::parse("Fairly common"), Some(Rarity::FairlyCommon));
+        assert_eq!(Rarity::parse(" Very  Rare "), Some(Rarity::VeryRare));
+        assert_eq!(Rarity::parse("Ultra-rare"), Some(Rarity::UltraRare));
+    }
+
+    #[test]
+    fn unknown_rarities() {
+        assert_eq!(Rarity::parse("Sometimes"), None);
+        assert_eq!(Rarity::parse(""), None);
+    }
+}
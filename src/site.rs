@@ -0,0 +1,325 @@
+use std::path::Path;
+use std::fs;
+use std::fmt::Write;
+use serde_json::{self as json, Value};
+use failure::Fallible;
+use itertools::Itertools;
+use crate::dataset::{self, Category, CATEGORIES};
+use crate::manifest::Manifest;
+use crate::location::{Place, Water};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun",
+    "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+const STYLE: &str = r#"
+body { font-family: sans-serif; max-width: 60em; margin: 0 auto; padding: 1em; }
+a { color: #2a6f4e; text-decoration: none; }
+nav { margin-bottom: 1em; }
+ul.items { list-style: none; padding: 0; display: flex; flex-wrap: wrap; }
+ul.items li { width: 8em; margin: 0.5em; text-align: center; }
+ul.items img { width: 64px; height: 64px; }
+img.item { width: 256px; height: 256px; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.5em; text-align: left; }
+td.month { width: 2.5em; text-align: center; }
+td.available { background: #8fd19e; }
+input#search { width: 100%; font-size: 1.2em; padding: 0.3em; }
+"#;
+
+const SEARCH_SCRIPT: &str = r#"
+document.getElementById("search").addEventListener("input", function (event) {
+    var query = event.target.value.trim().toLowerCase();
+    document.querySelectorAll("ul.items li").forEach(function (item) {
+        var matches = item.getAttribute("data-search").indexOf(query) !== -1;
+        item.style.display = matches ? "" : "none";
+    });
+});
+"#;
+
+/// Renders a self-contained static website from the dumped data and images.
+pub fn generate(data_dir: impl AsRef<Path>, image_dir: impl AsRef<Path>, out_dir: impl AsRef<Path>) -> Fallible<()> {
+    let data_dir = data_dir.as_ref();
+    let image_dir = image_dir.as_ref();
+    let out_dir = out_dir.as_ref();
+    let manifest = Manifest::load(image_dir)?;
+
+    fs::create_dir_all(out_dir)?;
+    fs::write(out_dir.join("style.css"), STYLE)?;
+    fs::write(out_dir.join("search.js"), SEARCH_SCRIPT)?;
+
+    let mut index = String::new();
+    writeln!(index, "<h1>Catalog</h1>")?;
+    writeln!(index, "<ul>")?;
+
+    for category in CATEGORIES {
+        let items = dataset::load(data_dir, category)?;
+
+        if items.is_empty() {
+            continue;
+        }
+
+        println!("Rendering {} {}", items.len(), category.name);
+
+        let category_dir = out_dir.join(category.name);
+        fs::create_dir_all(&category_dir)?;
+
+        for item in &items {
+            let image = dataset::image(category, item, &manifest)
+                .filter(|image| copy_image(item, image, image_dir, out_dir).unwrap_or(false));
+
+            if let Some(id) = dataset::id(item) {
+                let page = render_item(item, image.as_deref())?;
+                let page = layout(&title(item), 1, &page);
+                fs::write(category_dir.join(format!("{}.html", id)), page)?;
+            }
+        }
+
+        let page = render_category(category, &items, &manifest)?;
+        let page = layout(category.title, 1, &page);
+        fs::write(category_dir.join("index.html"), page)?;
+
+        writeln!(index, "<li><a href=\"{}/index.html\">{}</a> ({})</li>", category.name, category.title, items.len())?;
+    }
+
+    writeln!(index, "</ul>")?;
+    fs::write(out_dir.join("index.html"), layout("Catalog", 0, &index))?;
+
+    Ok(())
+}
+
+/// Copies the image into the site, returns whether it exists.
+fn copy_image(item: &Value, image: &str, image_dir: &Path, out_dir: &Path) -> Fallible<bool> {
+    let source = image_dir.join(image);
+
+    if !source.exists() {
+        eprintln!("Missing image '{}' of '{}'", source.display(), title(item));
+        return Ok(false);
+    }
+
+    let target = out_dir.join("images").join(image);
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::copy(source, target)?;
+
+    Ok(true)
+}
+
+fn layout(title: &str, depth: usize, body: &str) -> String {
+    let root = "../".repeat(depth);
+
+    format!(
+r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<link rel="stylesheet" href="{root}style.css">
+</head>
+<body>
+<nav><a href="{root}index.html">Catalog</a></nav>
+{body}
+</body>
+</html>
+"#,
+        title = escape(title),
+        root = root,
+        body = body,
+    )
+}
+
+fn render_category(category: &Category, items: &[Value], manifest: &Manifest) -> Fallible<String> {
+    let mut page = String::new();
+
+    writeln!(page, "<h1>{}</h1>", category.title)?;
+    writeln!(page, "<input id=\"search\" type=\"search\" placeholder=\"Search…\" autofocus>")?;
+
+    // Fish are grouped by the water they live in, everything else is one list
+    let grouped = items.iter().any(|item| water(item).is_some());
+    let groups = if grouped {
+        items.iter()
+            .sorted_by_key(|item| (water(item).is_none(), water(item)))
+            .group_by(|item| water(item))
+            .into_iter()
+            .map(|(water, items)| (water, items.collect_vec()))
+            .collect_vec()
+    } else {
+        vec![(None, items.iter().collect_vec())]
+    };
+
+    for (water, items) in groups {
+        match water {
+            Some(water) => writeln!(page, "<h2>{}</h2>", water.label("en").unwrap_or_default())?,
+            None if grouped => writeln!(page, "<h2>Other</h2>")?,
+            None => {},
+        }
+
+        render_items(&mut page, category, &items, manifest)?;
+    }
+
+    writeln!(page, "<script src=\"../search.js\"></script>")?;
+
+    Ok(page)
+}
+
+fn render_items(page: &mut String, category: &Category, items: &[&Value], manifest: &Manifest) -> Fallible<()> {
+    writeln!(page, "<ul class=\"items\">")?;
+
+    for item in items {
+        let id = match dataset::id(item) {
+            Some(id) => id,
+            None => continue,
+        };
+        let search = names(item)
+            .map(|(_, name)| name.to_lowercase())
+            .join(" ");
+
+        writeln!(page, "<li data-search=\"{}\"><a href=\"{}.html\">", escape(&search), id)?;
+
+        if let Some(image) = dataset::image(category, item, manifest) {
+            writeln!(page, "<img src=\"../images/{}\" alt=\"\" loading=\"lazy\"><br>", escape(&image))?;
+        }
+
+        writeln!(page, "{}</a></li>", escape(&title(item)))?;
+    }
+
+    writeln!(page, "</ul>")?;
+
+    Ok(())
+}
+
+/// Water of the first fishing spot of a bug or fish.
+fn water(item: &Value) -> Option<Water> {
+    item.get("location")?
+        .as_array()?
+        .iter()
+        .filter_map(|location| location.get("place")?.as_str())
+        .filter_map(Place::from_code)
+        .find_map(Place::water)
+}
+
+fn render_item(item: &Value, image: Option<&str>) -> Fallible<String> {
+    let mut page = String::new();
+    let fields = match item.as_object() {
+        Some(fields) => fields,
+        None => return Ok(page),
+    };
+
+    writeln!(page, "<h1>{}</h1>", escape(&title(item)))?;
+
+    if let Some(image) = image {
+        writeln!(page, "<img class=\"item\" src=\"../images/{}\" alt=\"\">", escape(image))?;
+    }
+
+    for (field, value) in fields {
+        match (&**field, value) {
+            ("id", _) => {},
+            ("months_north", _) => render_months(&mut page, "Northern hemisphere", value)?,
+            ("months_south", _) => render_months(&mut page, "Southern hemisphere", value)?,
+            ("time", Value::Array(slots)) => render_time(&mut page, slots)?,
+            ("prices", Value::Object(prices)) => {
+                for (kind, price) in prices {
+                    if let Some(price) = price.as_i64() {
+                        writeln!(page, "<p><b>{} price:</b> {} Bells</p>", escape(&label(kind)), price)?;
+                    }
+                }
+            },
+            (_, Value::Object(map)) if map.values().all(Value::is_string) => {
+                writeln!(page, "<h2>{}</h2>", escape(&label(field)))?;
+                writeln!(page, "<table>")?;
+                for (language, text) in map {
+                    let text = text.as_str().unwrap_or_default();
+                    writeln!(page, "<tr><th>{}</th><td>{}</td></tr>", escape(language), escape(text))?;
+                }
+                writeln!(page, "</table>")?;
+            },
+            (_, Value::Null) => {},
+            (_, value) => {
+                let value = match value {
+                    Value::String(text) => text.clone(),
+                    value => json::to_string(value)?,
+                };
+
+                writeln!(page, "<p><b>{}:</b> {}</p>", escape(&label(field)), escape(&value))?;
+            },
+        }
+    }
+
+    Ok(page)
+}
+
+fn render_months(page: &mut String, title: &str, months: &Value) -> Fallible<()> {
+    let months = match months.as_array() {
+        Some(months) => months,
+        None => return Ok(()),
+    };
+
+    writeln!(page, "<h2>{}</h2>", title)?;
+    writeln!(page, "<table><tr>")?;
+
+    for month in &MONTHS {
+        writeln!(page, "<th>{}</th>", month)?;
+    }
+
+    writeln!(page, "</tr><tr>")?;
+
+    for available in months {
+        let class = if available.as_bool() == Some(true) { "month available" } else { "month" };
+        writeln!(page, "<td class=\"{}\"></td>", class)?;
+    }
+
+    writeln!(page, "</tr></table>")?;
+
+    Ok(())
+}
+
+fn render_time(page: &mut String, slots: &[Value]) -> Fallible<()> {
+    let slots = slots.iter()
+        .filter_map(|slot| Some((slot.get(0)?.as_u64()?, slot.get(1)?.as_u64()?)))
+        .map(|(start, end)| match (start, end) {
+            (0, 24) => "All day".to_owned(),
+            (start, end) => format!("{}:00 - {}:00", start, end),
+        })
+        .join(", ");
+
+    writeln!(page, "<p><b>Time:</b> {}</p>", escape(&slots))?;
+
+    Ok(())
+}
+
+fn names(item: &Value) -> impl Iterator<Item = (&String, &str)> {
+    item.get("name")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter_map(|(language, name)| Some((language, name.as_str()?)))
+}
+
+fn title(item: &Value) -> String {
+    dataset::english_name(item)
+        .map(<_>::into)
+        .or_else(|| names(item).next().map(|(_, name)| name.to_owned()))
+        .unwrap_or_else(|| "???".into())
+}
+
+fn label(field: &str) -> String {
+    let mut label = field.replace('_', " ");
+
+    if let Some(first) = label.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+
+    label
+}
+
+fn escape(text: &str) -> String {
+    text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
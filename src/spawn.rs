@@ -0,0 +1,236 @@
+use serde::*;
+use crate::common::words;
+use crate::details::Details;
+use crate::location::{Location, Place, Modifier};
+
+/// A condition a critter needs to show up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all="snake_case")]
+pub enum SpawnCondition {
+    /// Only while it rains or snows
+    Raining,
+    /// Never while it rains or snows
+    NotRaining,
+    Stumps,
+    Rocks,
+    Villagers,
+    RottenTurnips,
+    Trash,
+    Snowballs,
+    PalmTrees,
+    LightSources,
+}
+
+/// (condition, phrases of the detail page text that imply it)
+const PHRASES: &[(SpawnCondition, &[&str])] = &[
+    (SpawnCondition::Raining, &[
+        "only appears when it is raining",
+        "only appears when it's raining",
+        "only appears during rain",
+        "only appear when it is raining",
+        "only appear during rain",
+        "only be found when it is raining",
+        "only be found during rain",
+        "only be caught when it is raining",
+        "only be caught during rain",
+        "only on rainy days",
+        "only appears on rainy days",
+        "only appear on rainy days",
+    ]),
+    (SpawnCondition::NotRaining, &[
+        "except when it is raining",
+        "except when it's raining",
+        "except during rain",
+        "unless it is raining",
+        "unless it's raining",
+        "does not appear when it is raining",
+        "does not appear during rain",
+        "doesn't appear when it is raining",
+        "doesn't appear during rain",
+        "will not appear when it is raining",
+        "won't appear when it is raining",
+        "won't appear during rain",
+        "not on rainy days",
+        "except on rainy days",
+    ]),
+    (SpawnCondition::Stumps, &["tree stump", "on stumps"]),
+    (SpawnCondition::Villagers, &["on villagers", "from villagers", "on a villager", "villager's head"]),
+    (SpawnCondition::RottenTurnips, &["rotten turnip"]),
+    (SpawnCondition::Trash, &["near trash", "on trash", "spoiled turnip"]),
+    (SpawnCondition::Snowballs, &["snowball"]),
+];
+
+/// (condition, whole values of the weather field of detail pages that imply it)
+const WEATHER_VALUES: &[(SpawnCondition, &[&str])] = &[
+    (SpawnCondition::Raining, &[
+        "rain",
+        "raining",
+        "rain only",
+        "only rain",
+        "only when raining",
+        "rainy days",
+        "rainy days only",
+        "rain or snow",
+        "raining or snowing",
+    ]),
+    (SpawnCondition::NotRaining, &[
+        "except rain",
+        "any except rain",
+        "any but rain",
+        "except when raining",
+        "except when it is raining",
+        "not when raining",
+        "not raining",
+        "no rain",
+        "not on rainy days",
+        "any except rain or snow",
+    ]),
+];
+
+impl SpawnCondition {
+    /// A note on the weather the condition needs, if it depends on the weather.
+    pub fn weather_note(self) -> Option<&'static str> {
+        match self {
+            SpawnCondition::Raining => Some("Only while it rains or snows"),
+            SpawnCondition::NotRaining => Some("Not while it rains or snows"),
+            _ => None,
+        }
+    }
+}
+
+/// Derives the spawn conditions from the typed locations and the detail page, if there is one.
+pub fn conditions(locations: &[Location], details: Option<&Details>) -> Vec<SpawnCondition> {
+    let mut conditions = Vec::new();
+
+    for location in locations {
+        if let Location::Known { place, modifiers } = location {
+            let condition = match place {
+                Place::Stumps => Some(SpawnCondition::Stumps),
+                Place::Rocks | Place::BeachRocks => Some(SpawnCondition::Rocks),
+                Place::Villagers => Some(SpawnCondition::Villagers),
+                Place::RottenTurnips => Some(SpawnCondition::RottenTurnips),
+                Place::Trash => Some(SpawnCondition::Trash),
+                Place::Snowballs => Some(SpawnCondition::Snowballs),
+                Place::PalmTrees => Some(SpawnCondition::PalmTrees),
+                Place::Lights => Some(SpawnCondition::LightSources),
+                _ => None,
+            };
+
+            conditions.extend(condition);
+
+            if modifiers.contains(&Modifier::Raining) {
+                conditions.push(SpawnCondition::Raining);
+            }
+        }
+    }
+
+    if let Some(details) = details {
+        if let Some(weather) = details.field("weather") {
+            conditions.extend(weather_condition(weather));
+        }
+
+        for &(condition, phrases) in PHRASES {
+            if details.mentions(phrases) {
+                conditions.push(condition);
+            }
+        }
+    }
+
+    conditions.sort();
+    conditions.dedup();
+
+    if conditions.contains(&SpawnCondition::Raining) && conditions.contains(&SpawnCondition::NotRaining) {
+        eprintln!("Contradicting rain conditions, dropping both: {:?}", locations);
+        conditions.retain(|&condition| condition != SpawnCondition::Raining && condition != SpawnCondition::NotRaining);
+    }
+
+    conditions
+}
+
+/// The condition of a weather field like "Rain only" or "Any except rain",
+/// which has to be one of the known values as a whole.
+fn weather_condition(weather: &str) -> Option<SpawnCondition> {
+    let weather = words(weather).join(" ");
+
+    WEATHER_VALUES.iter()
+        .find(|(_, values)| values.contains(&&*weather))
+        .map(|&(condition, _)| condition)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(weather: Option<&str>, paragraphs: &[&str]) -> Details {
+        Details {
+            fields: weather.into_iter().map(|weather| ("weather".into(), weather.into())).collect(),
+            paragraphs: paragraphs.iter().map(|&paragraph| paragraph.into()).collect(),
+            ..Details::default()
+        }
+    }
+
+    #[test]
+    fn conditions_from_locations() {
+        let locations = Location::parse_list("Under rocks & On tree stumps");
+
+        assert_eq!(conditions(&locations, None), vec![SpawnCondition::Stumps, SpawnCondition::Rocks]);
+        assert_eq!(conditions(&Location::parse_list("River"), None), vec![]);
+        assert_eq!(
+            conditions(&Location::parse_list("Sea (Raining)"), None),
+            vec![SpawnCondition::Raining],
+        );
+    }
+
+    #[test]
+    fn conditions_from_weather_field() {
+        let river = Location::parse_list("River");
+
+        assert_eq!(conditions(&river, Some(&details(Some("Rain only"), &[]))), vec![SpawnCondition::Raining]);
+        assert_eq!(conditions(&river, Some(&details(Some("Any except rain"), &[]))), vec![SpawnCondition::NotRaining]);
+        assert_eq!(conditions(&river, Some(&details(Some("Any weather"), &[]))), vec![]);
+        assert_eq!(conditions(&river, Some(&details(Some("Except when raining"), &[]))), vec![SpawnCondition::NotRaining]);
+        assert_eq!(conditions(&river, Some(&details(Some("Not on rainy days"), &[]))), vec![SpawnCondition::NotRaining]);
+    }
+
+    #[test]
+    fn weather_field_needs_a_whole_known_value() {
+        let river = Location::parse_list("River");
+
+        assert_eq!(conditions(&river, Some(&details(Some("Raining or not"), &[]))), vec![]);
+        assert_eq!(conditions(&river, Some(&details(Some("Any, more common when raining"), &[]))), vec![]);
+        assert_eq!(conditions(&river, Some(&details(Some("Nothing special"), &[]))), vec![]);
+    }
+
+    #[test]
+    fn conditions_from_text() {
+        let details = details(None, &["The tarantula can be found on the ground, except when it is raining."]);
+
+        assert_eq!(conditions(&Location::parse_list("Ground"), Some(&details)), vec![SpawnCondition::NotRaining]);
+    }
+
+    #[test]
+    fn passing_mentions_of_rain_in_text() {
+        let ground = Location::parse_list("Ground");
+        let passing = details(None, &["Like many bugs, it hides on rainy days in the game's predecessors, raining or not."]);
+        let not_on_rainy_days = details(None, &["It can be found on the ground, but not on rainy days."]);
+        let only_on_rainy_days = details(None, &["Snails show up on rocks only on rainy days."]);
+
+        assert_eq!(conditions(&ground, Some(&passing)), vec![]);
+        assert_eq!(conditions(&ground, Some(&not_on_rainy_days)), vec![SpawnCondition::NotRaining]);
+        assert_eq!(conditions(&ground, Some(&only_on_rainy_days)), vec![SpawnCondition::Raining]);
+    }
+
+    #[test]
+    fn phrases_match_whole_words() {
+        let details = details(None, &["It was spotted near a stumpy tree, and loves snowballs."]);
+
+        assert_eq!(conditions(&Location::parse_list("Ground"), Some(&details)), vec![SpawnCondition::Snowballs]);
+    }
+
+    #[test]
+    fn contradicting_rain_conditions_are_dropped() {
+        let details = details(Some("Except rain"), &[]);
+
+        assert_eq!(conditions(&Location::parse_list("Pond (Raining)"), Some(&details)), vec![]);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::labels::{self, Row};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Species {
+    Alligator,
+    Anteater,
+    Bear,
+    Bird,
+    Bull,
+    Cat,
+    Chicken,
+    Cow,
+    Cub,
+    Deer,
+    Dog,
+    Duck,
+    Eagle,
+    Elephant,
+    Frog,
+    Goat,
+    Gorilla,
+    Hamster,
+    Hippo,
+    Horse,
+    Kangaroo,
+    Koala,
+    Lion,
+    Monkey,
+    Mouse,
+    Octopus,
+    Ostrich,
+    Penguin,
+    Pig,
+    Rabbit,
+    Rhino,
+    Sheep,
+    Squirrel,
+    Tiger,
+    Wolf,
+}
+
+/// (species, code, english label, german label, further spellings)
+///
+/// The german wiki groups villagers under plural headings, so plurals are accepted as well.
+const SPECIES: &[Row<Species>] = &[
+    (Species::Alligator, "alligator", "Alligator", "Alligator", &["alligatoren", "krokodil", "krokodile"]),
+    (Species::Anteater, "anteater", "Anteater", "Ameisenbär", &["ameisenbären"]),
+    (Species::Bear, "bear", "Bear", "Bär", &["bären"]),
+    (Species::Bird, "bird", "Bird", "Vogel", &["vögel"]),
+    (Species::Bull, "bull", "Bull", "Stier", &["stiere", "bulle", "bullen"]),
+    (Species::Cat, "cat", "Cat", "Katze", &["katzen"]),
+    (Species::Chicken, "chicken", "Chicken", "Huhn", &["hühner"]),
+    (Species::Cow, "cow", "Cow", "Kuh", &["kühe"]),
+    (Species::Cub, "cub", "Cub", "Bärchen", &["jungbär", "jungbären", "bärenjunges", "bärenjungen"]),
+    (Species::Deer, "deer", "Deer", "Hirsch", &["hirsche", "reh", "rehe"]),
+    (Species::Dog, "dog", "Dog", "Hund", &["hunde"]),
+    (Species::Duck, "duck", "Duck", "Ente", &["enten"]),
+    (Species::Eagle, "eagle", "Eagle", "Adler", &[]),
+    (Species::Elephant, "elephant", "Elephant", "Elefant", &["elefanten"]),
+    (Species::Frog, "frog", "Frog", "Frosch", &["frösche"]),
+    (Species::Goat, "goat", "Goat", "Ziege", &["ziegen"]),
+    (Species::Gorilla, "gorilla", "Gorilla", "Gorilla", &["gorillas"]),
+    (Species::Hamster, "hamster", "Hamster", "Hamster", &[]),
+    (Species::Hippo, "hippo", "Hippo", "Nilpferd", &["nilpferde", "flusspferd", "flusspferde"]),
+    (Species::Horse, "horse", "Horse", "Pferd", &["pferde"]),
+    (Species::Kangaroo, "kangaroo", "Kangaroo", "Känguru", &["kängurus"]),
+    (Species::Koala, "koala", "Koala", "Koala", &["koalas"]),
+    (Species::Lion, "lion", "Lion", "Löwe", &["löwen"]),
+    (Species::Monkey, "monkey", "Monkey", "Affe", &["affen"]),
+    (Species::Mouse, "mouse", "Mouse", "Maus", &["mäuse"]),
+    (Species::Octopus, "octopus", "Octopus", "Oktopus", &["oktopusse", "krake", "kraken"]),
+    (Species::Ostrich, "ostrich", "Ostrich", "Strauß", &["strauße", "strauss"]),
+    (Species::Penguin, "penguin", "Penguin", "Pinguin", &["pinguine"]),
+    (Species::Pig, "pig", "Pig", "Schwein", &["schweine"]),
+    (Species::Rabbit, "rabbit", "Rabbit", "Hase", &["hasen", "kaninchen"]),
+    (Species::Rhino, "rhino", "Rhino", "Nashorn", &["nashörner"]),
+    (Species::Sheep, "sheep", "Sheep", "Schaf", &["schafe"]),
+    (Species::Squirrel, "squirrel", "Squirrel", "Eichhörnchen", &[]),
+    (Species::Tiger, "tiger", "Tiger", "Tiger", &[]),
+    (Species::Wolf, "wolf", "Wolf", "Wolf", &["wölfe"]),
+];
+
+impl_labels!(Species, SPECIES);
+
+impl Species {
+    /// Parses english and german spellings, ignoring case and surrounding punctuation.
+    pub fn parse(text: impl AsRef<str>) -> Option<Self> {
+        labels::parse(SPECIES, text.as_ref())
+    }
+}
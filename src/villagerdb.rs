@@ -27,10 +27,19 @@ pub fn get_villager_db_name(name: &str) -> String {
     }
 }
 
+pub fn get_item(id: &str) -> Fallible<Item> {
+    let path = format!("villagerdb/data/items/{}.json", id);
+    let data = fs::read(path)?;
+    let item = serde_json::from_slice::<Item>(&data)?;
+    Ok(item)
+}
+
 #[derive(Deserialize)]
 pub struct Villager {
     pub name: String,
     pub species: String,
+    /// Formatted as "{month}-{day}"
+    pub birthday: Option<String>,
     pub games: GamesVillager,
 }
 
@@ -40,8 +49,75 @@ pub struct GamesVillager {
 }
 
 #[derive(Deserialize)]
+#[serde(rename_all="camelCase")]
 pub struct NHVillager {
     pub personality: String,
     pub phrase: String,
+    /// The saying shown on the back of the villager's photo
+    pub quote: Option<String>,
     pub song: Option<String>,
+    pub hobby: Option<String>,
+    #[serde(default, alias="favoriteColors")]
+    pub colors: Vec<String>,
+    #[serde(default, alias="favoriteStyles")]
+    pub styles: Vec<String>,
+    /// Item IDs
+    pub clothes: Option<String>,
+    pub umbrella: Option<String>,
+    pub wallpaper: Option<String>,
+    pub flooring: Option<String>,
+    pub sign: Option<String>,
+    #[serde(default, alias="furniture", alias="houseItems")]
+    pub house_interior_items: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct Item {
+    pub name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn current_field_names() {
+        let villager = serde_json::from_str::<NHVillager>(r#"{
+            "personality": "Lazy",
+            "phrase": "duckling",
+            "colors": ["Blue", "Yellow"],
+            "styles": ["Simple"],
+            "clothes": "simple-parka",
+            "houseInteriorItems": ["bonsai-shelf", "ironwood-bed"]
+        }"#).unwrap();
+
+        assert_eq!(villager.colors, ["Blue", "Yellow"]);
+        assert_eq!(villager.styles, ["Simple"]);
+        assert_eq!(villager.clothes.as_deref(), Some("simple-parka"));
+        assert_eq!(villager.house_interior_items, ["bonsai-shelf", "ironwood-bed"]);
+    }
+
+    #[test]
+    fn alternative_field_names() {
+        let villager = serde_json::from_str::<NHVillager>(r#"{
+            "personality": "Lazy",
+            "phrase": "duckling",
+            "favoriteColors": ["Blue"],
+            "favoriteStyles": ["Simple"],
+            "furniture": ["bonsai-shelf"]
+        }"#).unwrap();
+
+        assert_eq!(villager.colors, ["Blue"]);
+        assert_eq!(villager.styles, ["Simple"]);
+        assert_eq!(villager.house_interior_items, ["bonsai-shelf"]);
+
+        let villager = serde_json::from_str::<NHVillager>(r#"{
+            "personality": "Lazy",
+            "phrase": "duckling",
+            "houseItems": ["ironwood-bed"]
+        }"#).unwrap();
+
+        assert_eq!(villager.house_interior_items, ["ironwood-bed"]);
+        assert!(villager.colors.is_empty());
+    }
 }
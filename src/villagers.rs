@@ -5,9 +5,13 @@ use serde::*;
 use failure::{Fallible};
 use itertools::Itertools;
 use crate::common::*;
+use crate::pipeline::{Pipeline, Fit};
 use crate::id;
 use rayon::prelude::*;
 use crate::villagerdb;
+use crate::personality::Personality;
+use crate::species::Species;
+use crate::game::Game;
 
 #[derive(Debug, Serialize)]
 pub struct Villager {
@@ -17,15 +21,37 @@ pub struct Villager {
     #[serde(skip)]
     pub image_url: Option<String>,
     pub is_new: bool,
-    pub species: String,
+    pub species: Option<Species>,
     pub gender: Gender,
     pub birthday: Option<[u8; 2]>,
     #[serde(rename="phrase")]
     pub phrases: BTreeMap<String, String>,
-    // #[serde(rename="photo_phrase")]
-    // pub photo_phrases: BTreeMap<String, String>,
-    #[serde(rename="personalities")]
-    pub personalities: BTreeMap<String, String>,
+    #[serde(rename="photo_phrase")]
+    pub photo_phrases: BTreeMap<String, String>,
+    pub personality: Option<Personality>,
+    pub hobby: Option<String>,
+    pub favorite_colors: Vec<String>,
+    pub favorite_styles: Vec<String>,
+    pub clothing: Option<ItemRef>,
+    pub umbrella: Option<ItemRef>,
+    pub wallpaper: Option<ItemRef>,
+    pub flooring: Option<ItemRef>,
+    pub sign: Option<ItemRef>,
+    pub house_interior_items: Vec<ItemRef>,
+    /// Earlier games this villager appeared in
+    pub appearances: Vec<Game>,
+    /// Profile fields of the german wiki that are not parsed (yet)
+    pub extra: BTreeMap<String, String>,
+    #[serde(skip)]
+    villagerdb_birthday: Option<[u8; 2]>,
+}
+
+/// An item referenced by its villagerdb ID.
+/// The name is only known if villagerdb has a record of the item.
+#[derive(Debug, Serialize)]
+pub struct ItemRef {
+    pub villagerdb_id: String,
+    pub name: Option<String>,
 }
 
 pub fn fetch_all() -> Fallible<Vec<Villager>> {
@@ -42,7 +68,13 @@ pub fn fetch_all() -> Fallible<Vec<Villager>> {
         let kind = kind.text();
         let kind = kind.trim();
 
-        let villagers = parse_table(&table, kind)?;
+        let species = Species::parse(kind);
+
+        if species.is_none() {
+            eprintln!("Unknown species '{}'", kind);
+        }
+
+        let villagers = parse_table(&table, species)?;
 
         all_villagers.extend(villagers)
     }
@@ -54,18 +86,75 @@ pub fn fetch_all() -> Fallible<Vec<Villager>> {
 
         assert_eq!(villager.names["eng"], db_villager.name);
 
-        villager.species = db_villager.species;
-        villager.phrases.insert("eng".into(), db_villager.games.nh.phrase);
-        villager.personalities.insert("eng".into(), db_villager.games.nh.personality);
+        let nh = db_villager.games.nh;
+
+        match (villager.species, Species::parse(&db_villager.species)) {
+            (_, None) => eprintln!("Unknown villagerdb species '{}' of '{}'", db_villager.species, name),
+            (Some(species), Some(db_species)) if species != db_species => {
+                eprintln!("Species of '{}' differs: wiki {:?}, villagerdb {:?}", name, species, db_species);
+                villager.species = Some(db_species);
+            },
+            (_, db_species) => villager.species = db_species,
+        }
+
+        villager.phrases.insert("eng".into(), nh.phrase);
+
+        if let Some(quote) = nh.quote {
+            villager.photo_phrases.insert("eng".into(), quote);
+        }
+
+        match Personality::parse(&nh.personality) {
+            Some(personality) => villager.personality = Some(personality),
+            None => eprintln!("Unknown villagerdb personality '{}' of '{}'", nh.personality, name),
+        }
+        villager.hobby = nh.hobby;
+        villager.favorite_colors = nh.colors;
+        villager.favorite_styles = nh.styles;
+        villager.clothing = nh.clothes.map(resolve_item);
+        villager.umbrella = nh.umbrella.map(resolve_item);
+        villager.wallpaper = nh.wallpaper.map(resolve_item);
+        villager.flooring = nh.flooring.map(resolve_item);
+        villager.sign = nh.sign.map(resolve_item);
+        villager.house_interior_items = nh.house_interior_items.into_iter().map(resolve_item).collect();
+        villager.villagerdb_birthday = db_villager.birthday.as_deref().and_then(parse_villagerdb_birthday);
     }
 
     all_villagers.par_iter_mut()
         .try_for_each(enrich_with_extra_info)?;
 
+    for villager in &all_villagers {
+        if let (Some(birthday), Some(db_birthday)) = (villager.birthday, villager.villagerdb_birthday) {
+            if birthday != db_birthday {
+                eprintln!(
+                    "Birthday of '{}' differs: wiki {:?}, villagerdb {:?}",
+                    villager.names["eng"], birthday, db_birthday,
+                );
+            }
+        }
+    }
+
     Ok(all_villagers)
 }
 
-fn parse_table(table: &Node, kind: &str) -> Fallible<Vec<Villager>> {
+fn resolve_item(villagerdb_id: String) -> ItemRef {
+    let name = villagerdb::get_item(&villagerdb_id)
+        .map_err(|err| eprintln!("Unknown villagerdb item '{}': {}", villagerdb_id, err))
+        .ok()
+        .map(|item| item.name);
+
+    ItemRef { villagerdb_id, name }
+}
+
+/// Parses villagerdb's "{month}-{day}" birthday format into `[day, month]`.
+fn parse_villagerdb_birthday(birthday: &str) -> Option<[u8; 2]> {
+    let (month, day) = birthday.split('-').collect_tuple()?;
+    let month = month.trim().parse().ok()?;
+    let day = day.trim().parse().ok()?;
+
+    Some([day, month])
+}
+
+fn parse_table(table: &Node, species: Option<Species>) -> Fallible<Vec<Villager>> {
     let rows = table.find(Name("tr"));
 
     let mut villagers = Vec::new();
@@ -144,12 +233,24 @@ fn parse_table(table: &Node, kind: &str) -> Fallible<Vec<Villager>> {
             names,
             image_url: img,
             is_new,
-            species: kind.to_owned(),
+            species,
             gender: Gender::Unknown,
             birthday: None,
             phrases: BTreeMap::new(),
-            // photo_phrases: BTreeMap::new(),
-            personalities: BTreeMap::new(),
+            photo_phrases: BTreeMap::new(),
+            personality: None,
+            hobby: None,
+            favorite_colors: Vec::new(),
+            favorite_styles: Vec::new(),
+            clothing: None,
+            umbrella: None,
+            wallpaper: None,
+            flooring: None,
+            sign: None,
+            house_interior_items: Vec::new(),
+            appearances: Vec::new(),
+            extra: BTreeMap::new(),
+            villagerdb_birthday: None,
         };
 
         villagers.push(villager);
@@ -170,68 +271,105 @@ fn enrich_with_extra_info(villager: &mut Villager) -> Fallible<()> {
             Class("wrap_nachbarntabelle")
                 .descendant(Name("table"))
             ).next().unwrap();
-        let rows = table.find(Name("tr")).skip(2);
-
-        for row in rows {
-            let cols = row.find(Name("th").or(Name("td"))).collect_vec();
-            let field = cols[0].text().trim().to_lowercase();
-            let value = cols[1].text().trim().to_owned();
-
-            match &*field {
-                "geschlecht" => match &*value.to_lowercase() {
-                    "weiblich" => villager.gender = Gender::Female,
-                    "männlich" => villager.gender = Gender::Male,
-                    gender => panic!("Unknown gender '{}'", gender),
-                },
-                "tierart" => {},
-                | "persönlichkeit"
-                | "persönlichkeit." => {
-                    villager.personalities.insert("deu".into(), value.trim().into());
-                },
-                "geburtstag" => {
-                    let parts = value.trim().split('.').map(str::trim).collect_vec();
-                    let day = parts[0].parse::<u8>().unwrap();
-                    let month = match parts[1] {
-                        "Januar" => 1,
-                        "Februar" => 2,
-                        "März" => 3,
-                        "April" => 4,
-                        "Mai" => 5,
-                        "Juni" => 6,
-                        "Juli" => 7,
-                        "August" => 8,
-                        "September" => 9,
-                        "Oktober" => 10,
-                        "November" => 11,
-                        "Dezember" => 12,
-                        month => panic!("unknown month '{}'", month),
-                    };
-                    villager.birthday = Some([day, month]);
-                },
-                "floskel" => {
-                    let phrase = value
-                        .trim()
-                        .replace('„', "")
-                        .replace('“', "")
-                        .replace('"', "");
-                    villager.phrases.insert("deu".into(), phrase);
-                },
-                "fotospruch" => {
-                    // villager.photo_phrases.insert("deu".into(), value.trim().into());
-                },
-                "auftreten" => {},
-                field => panic!("Unknown field '{}', value: '{}'", field, value),
-            }
 
-            // println!(">>> {:#?}", cols[0]);
+        for (field, value) in parse_profile_table(&table) {
+            apply_profile_field(villager, &field, value);
         }
-    } 
+    }
 
     // println!("{:#?}", main_table);
 
     Ok(())
 }
 
+/// Returns the (lowercase field, value) pairs of a villager profile table.
+fn parse_profile_table(table: &Node) -> Vec<(String, String)> {
+    table.find(Name("tr"))
+        .skip(2)
+        .filter_map(|row| {
+            let cols = row.find(Name("th").or(Name("td"))).collect_vec();
+            let field = cols.get(0)?.text().trim().trim_end_matches('.').to_lowercase();
+            let value = cols.get(1)?.text().trim().to_owned();
+
+            Some((field, value))
+        })
+        .collect()
+}
+
+fn apply_profile_field(villager: &mut Villager, field: &str, value: String) {
+    match field {
+        "geschlecht" => match &*value.to_lowercase() {
+            "weiblich" => villager.gender = Gender::Female,
+            "männlich" => villager.gender = Gender::Male,
+            _ => keep_unparsed(villager, field, value),
+        },
+        "tierart" => {},
+        "persönlichkeit" => {
+            match Personality::parse(&value) {
+                Some(personality) if villager.personality.is_none() => villager.personality = Some(personality),
+                Some(personality) if villager.personality != Some(personality) => eprintln!(
+                    "Personality of '{}' differs: wiki {:?}, villagerdb {:?}",
+                    villager.names["eng"], personality, villager.personality,
+                ),
+                Some(_) => {},
+                None => eprintln!("Unknown personality '{}' of '{}'", value, villager.names["eng"]),
+            }
+        },
+        "geburtstag" => match parse_birthday(&value) {
+            Some(birthday) => villager.birthday = Some(birthday),
+            None => keep_unparsed(villager, field, value),
+        },
+        "floskel" => {
+            villager.phrases.insert("deu".into(), strip_quotes(&value));
+        },
+        "fotospruch" => {
+            villager.photo_phrases.insert("deu".into(), strip_quotes(&value));
+        },
+        "auftreten" => {
+            villager.appearances = Game::parse_list(&value);
+        },
+        field => {
+            eprintln!("Unknown field '{}' of '{}', value: '{}'", field, villager.names["eng"], value);
+            villager.extra.insert(field.into(), value);
+        },
+    }
+}
+
+/// Keeps a known field whose value could not be parsed in `extra`.
+fn keep_unparsed(villager: &mut Villager, field: &str, value: String) {
+    eprintln!("Could not parse field '{}' of '{}', value: '{}'", field, villager.names["eng"], value);
+    villager.extra.insert(field.into(), value);
+}
+
+/// Parses german birthdays like "9. Juli" into `[day, month]`.
+fn parse_birthday(text: &str) -> Option<[u8; 2]> {
+    let parts = text.trim().split('.').map(str::trim).collect_vec();
+    let day = parts.first()?.parse::<u8>().ok()?;
+    let month = match *parts.get(1)? {
+        "Januar" => 1,
+        "Februar" => 2,
+        "März" => 3,
+        "April" => 4,
+        "Mai" => 5,
+        "Juni" => 6,
+        "Juli" => 7,
+        "August" => 8,
+        "September" => 9,
+        "Oktober" => 10,
+        "November" => 11,
+        "Dezember" => 12,
+        _ => return None,
+    };
+
+    Some([day, month])
+}
+
+fn strip_quotes(text: &str) -> String {
+    text
+        .trim()
+        .replace(&['„', '“', '"'][..], "")
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all="lowercase")]
 pub enum Gender {
@@ -252,20 +390,42 @@ impl HasFiles for Villager {
 
         if let Some(image_url) = overrides.get(&*name) {
             files.push(File {
-                name: format!("villagerdb/{}.png", name),
+                name: format!("villagerdb/{}", name),
+                id: self.id,
+                category: "villagers",
                 url: image_url.to_string(),
-                transform: convert_image_to_png,
+                pipeline: Pipeline::default().with_fit(Fit::Pad),
             });
 
             return files;
         }
 
         files.push(File {
-            name: format!("villagerdb/{}.png", name),
+            name: format!("villagerdb/{}", name),
+            id: self.id,
+            category: "villagers",
             url: format!("https://villagerdb.com/images/villagers/full/{}.png", villagerdb_name),
-            transform: convert_image_to_png,
+            pipeline: Pipeline::default().with_fit(Fit::Pad),
         });
 
         files
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn german_birthdays() {
+        assert_eq!(parse_birthday("9. Juli"), Some([9, 7]));
+        assert_eq!(parse_birthday(" 24. März "), Some([24, 3]));
+    }
+
+    #[test]
+    fn invalid_birthdays() {
+        assert_eq!(parse_birthday("Juli"), None);
+        assert_eq!(parse_birthday("9."), None);
+        assert_eq!(parse_birthday("9. Juillet"), None);
+        assert_eq!(parse_birthday(""), None);
+    }
+}